@@ -0,0 +1,94 @@
+use std::time::{Duration, Instant};
+use todo::query::Query;
+use todo::sort::Sort;
+use todo::todo::Todo;
+use todo::todos::{TodoStorage, Todos};
+
+const TODOS: u32 = 100_000;
+const ROUNDS: u32 = 20;
+const LOAD_BUDGET: Duration = Duration::from_secs(2);
+const PAGE_BUDGET: Duration = Duration::from_millis(50);
+const SAVE_BUDGET: Duration = Duration::from_millis(20);
+
+fn average(name: &str, rounds: u32, elapsed: Duration, budget: Duration, slow: &mut Vec<String>) {
+    let average = elapsed / rounds;
+    println!("{name:<32} {average:>12?}  (limite {budget:?})");
+    if average > budget {
+        slow.push(format!("{name} ({average:?} > {budget:?})"));
+    }
+}
+
+async fn seed(path: &str) {
+    let mut storage = Todos::new();
+    storage.parse_file_for_todos(path).await.unwrap();
+    for key in 1..=TODOS {
+        storage.insert_todo(Todo::new(format!(
+            "tarefa {key} #grupo{} due:2023-{:02}-01",
+            key % 10,
+            key % 12 + 1
+        )));
+        if key % 3 == 0 {
+            storage.resolve_one_todo(key);
+        }
+    }
+    storage.parse_map_write_file(path).await.unwrap();
+}
+
+#[tokio::main]
+async fn main() {
+    let dir = std::env::temp_dir().join(format!("todo_bench_{}", std::process::id()));
+    let path = dir.join("todo_list.txt").to_string_lossy().to_string();
+    seed(&path).await;
+
+    let mut slow = Vec::new();
+    let mut elapsed = Duration::ZERO;
+    let mut storage = Todos::new();
+    for _ in 0..ROUNDS {
+        storage = Todos::new();
+        let started = Instant::now();
+        storage.parse_file_for_todos(&path).await.unwrap();
+        elapsed += started.elapsed();
+    }
+    average("carregar 100k", ROUNDS, elapsed, LOAD_BUDGET, &mut slow);
+
+    let queries = [
+        ("tag:grupo3", 100),
+        ("status:open", 0),
+        ("due<=2023-02-01", 0),
+        ("", 1_999),
+    ];
+    for (query, number) in queries {
+        let parsed = Query::parse(query).unwrap();
+        let started = Instant::now();
+        for _ in 0..ROUNDS {
+            storage.page(&parsed, &Sort::default(), number, 50);
+        }
+        average(
+            &format!("página {number} de '{query}'"),
+            ROUNDS,
+            started.elapsed(),
+            PAGE_BUDGET,
+            &mut slow,
+        );
+    }
+
+    let started = Instant::now();
+    for round in 0..ROUNDS {
+        let key = storage.insert_todo(Todo::new(format!("nova {round} #lote")));
+        storage.resolve_one_todo(key);
+        storage.parse_map_write_file(&path).await.unwrap();
+    }
+    average(
+        "inserir + salvar",
+        ROUNDS,
+        started.elapsed(),
+        SAVE_BUDGET,
+        &mut slow,
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+    if !slow.is_empty() {
+        eprintln!("❗ Acima do limite: {} ❗", slow.join(", "));
+        std::process::exit(1);
+    }
+}
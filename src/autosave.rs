@@ -0,0 +1,155 @@
+use crate::error::StorageError;
+use crate::todos::TodoStorage;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, Notify};
+use tokio::time::{sleep, sleep_until, Instant};
+
+const MAX_WAIT_FACTOR: u32 = 5;
+
+pub type SharedStorage = Arc<Mutex<dyn TodoStorage + Send>>;
+
+#[derive(Clone)]
+pub struct Autosave {
+    storage: SharedStorage,
+    path: String,
+    dirty: Arc<AtomicBool>,
+    changed: Arc<Notify>,
+    failure: Arc<std::sync::Mutex<Option<StorageError>>>,
+}
+
+impl Autosave {
+    pub fn spawn(storage: SharedStorage, path: String, delay: Duration) -> Self {
+        let autosave = Self {
+            storage,
+            path,
+            dirty: Arc::new(AtomicBool::new(false)),
+            changed: Arc::new(Notify::new()),
+            failure: Arc::new(std::sync::Mutex::new(None)),
+        };
+        tokio::spawn(autosave.clone().debounce(delay));
+        autosave
+    }
+
+    async fn debounce(self, delay: Duration) {
+        loop {
+            self.changed.notified().await;
+            let deadline = Instant::now() + delay * MAX_WAIT_FACTOR;
+            loop {
+                tokio::select! {
+                    _ = self.changed.notified() => {}
+                    _ = sleep(delay) => break,
+                    _ = sleep_until(deadline) => break,
+                }
+            }
+            if let Err(error) = self.flush().await {
+                if let Ok(mut failure) = self.failure.lock() {
+                    *failure = Some(error);
+                }
+            }
+        }
+    }
+
+    pub fn touch(&self) {
+        self.dirty.store(true, Ordering::SeqCst);
+        self.changed.notify_one();
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::SeqCst)
+    }
+
+    pub fn take_failure(&self) -> Option<StorageError> {
+        self.failure
+            .lock()
+            .ok()
+            .and_then(|mut failure| failure.take())
+    }
+
+    pub async fn flush(&self) -> Result<(), StorageError> {
+        let mut storage = self.storage.lock().await;
+        if !self.dirty.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let saved = storage.parse_map_write_file(&self.path).await;
+        if saved.is_err() {
+            self.dirty.store(true, Ordering::SeqCst);
+        }
+        saved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todos::MockTodoStorage;
+
+    fn shared(storage: MockTodoStorage) -> SharedStorage {
+        Arc::new(Mutex::new(storage))
+    }
+
+    #[tokio::test]
+    async fn test_rapid_changes_are_saved_once() {
+        let mut mock_storage = MockTodoStorage::new();
+        mock_storage
+            .expect_parse_map_write_file()
+            .times(1)
+            .returning(|_| Ok(()));
+        let autosave = Autosave::spawn(
+            shared(mock_storage),
+            "test_autosave.txt".to_string(),
+            Duration::from_millis(50),
+        );
+
+        tokio::time::pause();
+        for _ in 0..20 {
+            autosave.touch();
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(autosave.is_dirty());
+        sleep(Duration::from_millis(50)).await;
+        assert!(!autosave.is_dirty());
+        autosave.flush().await.unwrap();
+        assert!(autosave.take_failure().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_zero_delay_saves_each_change() {
+        let mut mock_storage = MockTodoStorage::new();
+        mock_storage
+            .expect_parse_map_write_file()
+            .times(3)
+            .returning(|_| Ok(()));
+        let autosave = Autosave::spawn(
+            shared(mock_storage),
+            "test_autosave.txt".to_string(),
+            Duration::ZERO,
+        );
+
+        tokio::time::pause();
+        for _ in 0..3 {
+            autosave.touch();
+            sleep(Duration::from_millis(1)).await;
+            assert!(!autosave.is_dirty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_save_stays_dirty() {
+        let mut mock_storage = MockTodoStorage::new();
+        mock_storage
+            .expect_parse_map_write_file()
+            .times(1)
+            .returning(|_| Err(StorageError::parse("disco cheio")));
+        let autosave = Autosave::spawn(
+            shared(mock_storage),
+            "test_autosave.txt".to_string(),
+            Duration::from_secs(60),
+        );
+
+        autosave.touch();
+        assert!(autosave.flush().await.is_err());
+        assert!(autosave.is_dirty());
+    }
+}
@@ -0,0 +1,228 @@
+use crate::crypto::Codec;
+use crate::error::StorageError;
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone, Utc};
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use tokio::fs::{create_dir_all, read_dir, remove_file};
+
+const NAME_FORMAT: &str = "%Y%m%dT%H%M%S%.3f";
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct BackupPolicy {
+    pub keep_last: usize,
+    pub keep_days: i64,
+    pub every: Duration,
+}
+
+impl Default for BackupPolicy {
+    fn default() -> Self {
+        Self {
+            keep_last: 10,
+            keep_days: 7,
+            every: Duration::zero(),
+        }
+    }
+}
+
+impl BackupPolicy {
+    fn retained<'a>(&self, backups: &'a [Backup], now: DateTime<Utc>) -> Vec<&'a Backup> {
+        let oldest_day = (now - Duration::days(self.keep_days)).with_timezone(&Local);
+        let mut days = HashSet::new();
+
+        backups
+            .iter()
+            .enumerate()
+            .filter(|(position, backup)| {
+                let local = backup.at.with_timezone(&Local);
+                let newest_of_day = local > oldest_day && days.insert(local.date_naive());
+                *position < self.keep_last.max(1) || newest_of_day
+            })
+            .map(|(_, backup)| backup)
+            .collect()
+    }
+}
+
+pub struct BackupSchedule {
+    policy: BackupPolicy,
+    last: Option<DateTime<Utc>>,
+}
+
+impl BackupSchedule {
+    pub fn new(policy: BackupPolicy) -> Self {
+        Self { policy, last: None }
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.last.is_none_or(|last| now - last >= self.policy.every)
+    }
+
+    pub async fn save(
+        &mut self,
+        todo_path: &str,
+        snapshot: &str,
+        codec: &Codec,
+    ) -> Result<(), StorageError> {
+        Backups::save(todo_path, snapshot, &self.policy, codec).await?;
+        self.last = Some(Utc::now());
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Backup {
+    pub name: String,
+    pub at: DateTime<Utc>,
+}
+
+pub struct Backups;
+
+impl Backups {
+    pub fn dir(todo_path: &str) -> String {
+        format!("{todo_path}.backups")
+    }
+
+    pub async fn save(
+        todo_path: &str,
+        snapshot: &str,
+        policy: &BackupPolicy,
+        codec: &Codec,
+    ) -> Result<(), StorageError> {
+        let dir = Self::dir(todo_path);
+        create_dir_all(&dir)
+            .await
+            .map_err(|err| StorageError::io(&dir, err))?;
+
+        let now = Utc::now();
+        let name = format!("{}.txt", now.format(NAME_FORMAT));
+        codec.write(&format!("{dir}/{name}"), snapshot).await?;
+
+        let backups = Self::list(todo_path).await?;
+        let retained = policy
+            .retained(&backups, now)
+            .into_iter()
+            .map(|backup| backup.name.clone())
+            .collect::<HashSet<String>>();
+        for backup in backups
+            .iter()
+            .filter(|backup| !retained.contains(&backup.name))
+        {
+            let path = Self::path(todo_path, backup);
+            remove_file(&path)
+                .await
+                .map_err(|err| StorageError::io(&path, err))?;
+        }
+        Ok(())
+    }
+
+    pub async fn list(todo_path: &str) -> Result<Vec<Backup>, StorageError> {
+        let dir = Self::dir(todo_path);
+        let mut entries = match read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(StorageError::io(&dir, err)),
+        };
+
+        let mut backups = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|err| StorageError::io(&dir, err))?
+        {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(stamp) = name.strip_suffix(".txt") else {
+                continue;
+            };
+            if let Ok(at) = NaiveDateTime::parse_from_str(stamp, NAME_FORMAT) {
+                backups.push(Backup {
+                    name,
+                    at: Utc.from_utc_datetime(&at),
+                });
+            }
+        }
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.at));
+        Ok(backups)
+    }
+
+    pub fn path(todo_path: &str, backup: &Backup) -> String {
+        format!("{}/{}", Self::dir(todo_path), backup.name)
+    }
+
+    pub async fn read(
+        todo_path: &str,
+        backup: &Backup,
+        codec: &Codec,
+    ) -> Result<String, StorageError> {
+        let path = Self::path(todo_path, backup);
+        codec
+            .read(&path)
+            .await?
+            .ok_or_else(|| StorageError::io(&path, std::io::Error::from(ErrorKind::NotFound)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup_at(at: DateTime<Utc>) -> Backup {
+        Backup {
+            name: format!("{}.txt", at.format(NAME_FORMAT)),
+            at,
+        }
+    }
+
+    #[test]
+    fn test_retained_keeps_last_and_one_per_day() {
+        let now = Utc::now();
+        let backups = (0..30)
+            .map(|hours| backup_at(now - Duration::hours(hours * 12)))
+            .collect::<Vec<Backup>>();
+        let policy = BackupPolicy {
+            keep_last: 3,
+            keep_days: 7,
+            ..BackupPolicy::default()
+        };
+
+        let retained = policy.retained(&backups, now);
+        assert!(retained.len() >= 3 + 5);
+        assert!(retained.len() <= 3 + 8);
+        assert!(retained
+            .iter()
+            .all(|backup| backup.at > now - Duration::days(8)));
+
+        let policy = BackupPolicy {
+            keep_last: 0,
+            keep_days: 0,
+            ..BackupPolicy::default()
+        };
+        let retained = policy.retained(&backups, now);
+        assert_eq!(retained.len(), 1);
+        assert_eq!(retained[0].name, backups[0].name);
+    }
+
+    #[tokio::test]
+    async fn test_save_and_list_backups() {
+        let path = "test_backup_todo.txt";
+        let policy = BackupPolicy {
+            keep_last: 2,
+            keep_days: 0,
+            ..BackupPolicy::default()
+        };
+        for snapshot in ["1-false-foo\n", "1-true-foo\n", "1-true-boo\n"] {
+            Backups::save(path, snapshot, &policy, &Codec::Plain)
+                .await
+                .unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        let backups = Backups::list(path).await.unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(
+            Backups::read(path, &backups[0], &Codec::Plain)
+                .await
+                .unwrap(),
+            "1-true-boo\n"
+        );
+        tokio::fs::remove_dir_all(Backups::dir(path)).await.unwrap();
+    }
+}
@@ -1,40 +1,215 @@
-use crate::command::UserCommand;
+use crate::autosave::Autosave;
+use crate::command::{Arrange, PageTurn, Subcommand, UserCommand};
+use crate::config::Config;
+use crate::error::StorageError;
+use crate::query::Query;
+use crate::search::Search;
+use crate::smart::SmartList;
+use crate::sort::Sort;
 use crate::terminal::{TerminalError, UserInterface};
-use crate::todos::TodoStorage;
+use crate::timeline;
+use crate::todo::Todo;
+use crate::todos::{Storage, Stores, TodoStorage};
+use crate::views::View;
+use chrono::{Local, Utc};
 use console::Style;
+use std::process;
+use std::time::Duration;
+use tokio::signal;
 
 pub struct TodoCli {
     pub user_interface: Box<dyn UserInterface>,
-    todo_storage: Box<dyn TodoStorage>,
+    stores: Stores,
+    autosave: Autosave,
+    config: Config,
 }
 
 impl TodoCli {
-    pub fn new<U: UserInterface + 'static, S: TodoStorage + 'static>(ui: U, storage: S) -> Self {
+    pub fn new<U: UserInterface + 'static, S: Storage + Send + 'static>(
+        ui: U,
+        storage: S,
+        config: Config,
+    ) -> Self {
+        Self::with_stores(ui, Stores::new(storage), config)
+    }
+
+    pub fn with_stores<U: UserInterface + 'static>(ui: U, stores: Stores, config: Config) -> Self {
+        let delay = if config.git {
+            Duration::ZERO
+        } else {
+            config.autosave
+        };
         Self {
             user_interface: Box::new(ui),
-            todo_storage: Box::new(storage),
+            autosave: Autosave::spawn(stores.todos.clone(), config.file.clone(), delay),
+            stores,
+            config,
         }
     }
 
     pub async fn run(&mut self) -> Result<(), TerminalError> {
-        self.todo_storage
-            .parse_file_for_todos("todo_list.txt")
+        self.unlock_storage().await?;
+        let report = self
+            .stores
+            .todos
+            .lock()
+            .await
+            .parse_file_for_todos(&self.config.file)
             .await?;
         self.user_interface
             .write_styled("Olá! 😃\n", Style::new().magenta())
             .await?;
+        self.user_interface
+            .write_styled(
+                &format!("📂 Arquivo: {}\n", self.config.file),
+                Style::new().white().dim(),
+            )
+            .await?;
+        self.user_interface.show_load_report(&report).await?;
+        if self.config.encrypt
+            && !self
+                .stores
+                .crypto
+                .lock()
+                .await
+                .is_encrypted(&self.config.file)
+                .await
+        {
+            self.change_passphrase().await?;
+        }
+        self.flush_on_interrupt();
+        if let Some(view) = self.config.view.clone() {
+            self.open_view(&view).await?;
+        }
+
+        let result = self.serve().await;
+        self.autosave.flush().await?;
+        result
+    }
+
+    pub async fn run_once(&mut self, subcommand: Subcommand) -> Result<(), TerminalError> {
+        self.unlock_storage().await?;
+        self.stores
+            .todos
+            .lock()
+            .await
+            .parse_file_for_todos(&self.config.file)
+            .await?;
+
+        let not_found =
+            |key: u32| TerminalError::NotFound(format!("❗ O TODO {key} não existe ❗"));
+        let output = match subcommand {
+            Subcommand::Add(message) => {
+                let key = self
+                    .stores
+                    .todos
+                    .lock()
+                    .await
+                    .insert_todo(Todo::new(message));
+                self.autosave.touch();
+                format!("{key}\n")
+            }
+            Subcommand::List(filter) => {
+                let query = Query::parse(&filter)?;
+                let sort = self.stores.views.lock().await.sort_for(query.source());
+                self.stores
+                    .todos
+                    .lock()
+                    .await
+                    .page(&query, &sort, 0, usize::MAX)
+                    .todos
+                    .iter()
+                    .map(|(key, todo)| {
+                        format!(
+                            "{key}\t{}\t{}\n",
+                            if todo.resolved { "[x]" } else { "[ ]" },
+                            todo.message.trim()
+                        )
+                    })
+                    .collect()
+            }
+            Subcommand::Done(key) => {
+                if !self.stores.todos.lock().await.resolve_one_todo(key) {
+                    return Err(not_found(key));
+                }
+                self.autosave.touch();
+                String::new()
+            }
+            Subcommand::Edit(key, message) => {
+                let mut storage = self.stores.todos.lock().await;
+                let current = storage.get_one_todo(key).ok_or_else(|| not_found(key))?;
+                let mut todo = Todo::new(message);
+                todo.notes = current.notes;
+                storage.update(key, todo);
+                self.autosave.touch();
+                String::new()
+            }
+            Subcommand::Note(key, notes) => {
+                let mut storage = self.stores.todos.lock().await;
+                let mut todo = storage.get_one_todo(key).ok_or_else(|| not_found(key))?;
+                todo.notes = notes;
+                storage.update(key, todo);
+                self.autosave.touch();
+                String::new()
+            }
+            Subcommand::Remove(key) => {
+                let mut storage = self.stores.todos.lock().await;
+                if storage.get_one_todo(key).is_none() {
+                    return Err(not_found(key));
+                }
+                storage.remove(key);
+                self.autosave.touch();
+                String::new()
+            }
+        };
+        self.autosave.flush().await?;
+        self.user_interface
+            .write_styled(&output, Style::new())
+            .await
+    }
 
+    async fn serve(&mut self) -> Result<(), TerminalError> {
         loop {
-            self.user_interface.show_options().await?;
+            self.follow_file().await?;
+            if let Some(error) = self.autosave.take_failure() {
+                self.user_interface.show_error(error.into());
+            }
+            if self.autosave.is_dirty() {
+                self.user_interface.show_unsaved().await?;
+            }
+            let views = self.stores.views.lock().await.views();
+            self.user_interface.show_options(&views).await?;
+            let command = self.user_interface.get_user_command().await?;
+            self.follow_file().await?;
 
-            match self.user_interface.get_user_command().await? {
+            match command {
                 UserCommand::Insert => self.add_todo().await?,
                 UserCommand::ShowTodos => self.show_todos().await?,
+                UserCommand::SmartList(list) => self.show_smart_list(list).await?,
                 UserCommand::Resolve => self.resolve_todo().await?,
                 UserCommand::Update => self.update_todo().await?,
                 UserCommand::Delete => self.delete_todo().await?,
+                UserCommand::Undo => self.undo_todos().await?,
+                UserCommand::Redo => self.redo_todos().await?,
+                UserCommand::TimeTravel => self.show_todos_at().await?,
+                UserCommand::Compare => self.compare_todos().await?,
+                UserCommand::Backups => self.manage_backups().await?,
+                UserCommand::Passphrase => self.change_passphrase().await?,
+                UserCommand::GitHistory => self.git_history().await?,
+                UserCommand::GitSync => self.git_sync().await?,
+                UserCommand::Sync => self.sync_todos().await?,
+                UserCommand::Renumber => self.renumber_todos().await?,
+                UserCommand::Trash => self.manage_trash().await?,
+                UserCommand::Search => self.search_todos().await?,
+                UserCommand::Views => self.manage_views().await?,
+                UserCommand::OpenView(name) => self.open_view(&name).await?,
+                UserCommand::Reorder => self.reorder_todos().await?,
+                UserCommand::Stats => self.show_stats().await?,
+                UserCommand::Report => self.export_report().await?,
+                UserCommand::Notes => self.edit_notes().await?,
                 UserCommand::Other => self.user_interface.show_invalid_option().await?,
                 UserCommand::Exit => {
+                    self.autosave.flush().await?;
                     self.user_interface.finish_todo().await?;
                     return Ok(());
                 }
@@ -42,41 +217,77 @@ impl TodoCli {
         }
     }
 
+    fn flush_on_interrupt(&self) {
+        let autosave = self.autosave.clone();
+        tokio::spawn(async move {
+            if signal::ctrl_c().await.is_ok() {
+                if let Err(error) = autosave.flush().await {
+                    eprintln!("❗ {error} ❗");
+                }
+                process::exit(130);
+            }
+        });
+    }
+
     async fn add_todo(&mut self) -> Result<(), TerminalError> {
         self.user_interface.clean()?;
         let todo = self.user_interface.ask_for_new_todo().await?;
         self.user_interface.show_todo(&todo, "\n✅: ").await?;
-        self.todo_storage.insert_todo(todo);
-        self.todo_storage
-            .parse_map_write_file("todo_list.txt")
-            .await?;
+        self.stores.todos.lock().await.insert_todo(todo);
+        self.autosave.touch();
 
         Ok(())
     }
 
     async fn show_todos(&mut self) -> Result<(), TerminalError> {
         self.user_interface.clean()?;
+        let query = match self.user_interface.ask_query().await {
+            Ok(query) => query,
+            Err(error) => {
+                self.user_interface.show_error(error);
+                return Ok(());
+            }
+        };
+        let current = self.stores.views.lock().await.sort_for(query.source());
+        let sort = match self.user_interface.ask_sort(&current).await {
+            Ok(sort) => sort,
+            Err(error) => {
+                self.user_interface.show_error(error);
+                return Ok(());
+            }
+        };
+        if self
+            .stores
+            .views
+            .lock()
+            .await
+            .remember_sort(query.source(), sort)
+        {
+            self.autosave.touch();
+        }
         self.user_interface
-            .write_styled("\nOs seus TODO's são: 📖\n\n", Style::new().blue().bold())
+            .write_styled(
+                &format!("\nOs seus TODO's são (ordem: {sort}): 📖\n\n"),
+                Style::new().blue().bold(),
+            )
             .await?;
-        self.show_all_todos(false).await?;
+        self.show_page(&query, &sort, false).await?;
         Ok(())
     }
 
     async fn update_todo(&mut self) -> Result<(), TerminalError> {
         self.user_interface.clean()?;
-        while self.check_list_is_empty(&*self.todo_storage) {
+        while self.check_list_is_empty(&*self.stores.todos.lock().await) {
             self.show_all_todos(true).await?;
             self.user_interface.ask_key_todo_update().await?;
 
             match self.user_interface.parse_user_option().await {
                 Ok(key) => {
-                    if self.todo_is_found(key, "").await? {
-                        let todo = self.user_interface.ask_for_new_todo().await?;
-                        self.todo_storage.update(key, todo);
-                        self.todo_storage
-                            .parse_map_write_file("todo_list.txt")
-                            .await?;
+                    if let Some(current) = self.todo_is_found(key, "").await? {
+                        let mut todo = self.user_interface.ask_for_new_todo().await?;
+                        todo.notes = current.notes;
+                        self.stores.todos.lock().await.update(key, todo);
+                        self.autosave.touch();
                         self.user_interface
                             .write_feedback("\n✅ TODO atualizado com sucesso! ✅\n")
                             .await?;
@@ -92,21 +303,20 @@ impl TodoCli {
         Ok(())
     }
 
-    async fn delete_todo(&mut self) -> Result<(), TerminalError> {
+    async fn edit_notes(&mut self) -> Result<(), TerminalError> {
         self.user_interface.clean()?;
-        while self.check_list_is_empty(&*self.todo_storage) {
+        while self.check_list_is_empty(&*self.stores.todos.lock().await) {
             self.show_all_todos(true).await?;
-            self.user_interface.ask_key_todo_delete().await?;
+            self.user_interface.ask_key_todo_update().await?;
 
             match self.user_interface.parse_user_option().await {
                 Ok(key) => {
-                    if self
-                        .todo_is_found(key, "\n❌ O TODO foi excluído com sucesso! ❌\n")
-                        .await?
-                    {
-                        self.todo_storage.remove(key);
-                        self.todo_storage
-                            .parse_map_write_file("todo_list.txt")
+                    if let Some(mut todo) = self.todo_is_found(key, "").await? {
+                        todo.notes = self.user_interface.ask_for_notes(&todo.notes).await?;
+                        self.stores.todos.lock().await.update(key, todo);
+                        self.autosave.touch();
+                        self.user_interface
+                            .write_feedback("\n📝 Notas atualizadas com sucesso! 📝\n")
                             .await?;
                         return Ok(());
                     }
@@ -120,46 +330,21 @@ impl TodoCli {
         Ok(())
     }
 
-    fn check_list_is_empty(&self, list: &dyn TodoStorage) -> bool {
-        if list.is_empty() < 1 {
-            self.user_interface.show_error(TerminalError::NotFound(
-                "A sua coleção de TODOs esta vazia".to_string(),
-            ));
-            return false;
-        }
-        true
-    }
-
-    async fn show_all_todos(&mut self, show_keys: bool) -> Result<(), TerminalError> {
-        for (key, todo) in self.todo_storage.get_collection() {
-            if show_keys {
-                self.user_interface
-                    .show_todo(todo, format!("{key}: ").as_str())
-                    .await?;
-            } else {
-                self.user_interface.show_todo(todo, "⏳: ").await?;
-            }
-        }
-
-        Ok(())
-    }
-
-    async fn resolve_todo(&mut self) -> Result<(), TerminalError> {
+    async fn delete_todo(&mut self) -> Result<(), TerminalError> {
         self.user_interface.clean()?;
-        while self.check_list_is_empty(&*self.todo_storage) {
+        while self.check_list_is_empty(&*self.stores.todos.lock().await) {
             self.show_all_todos(true).await?;
-            self.user_interface.get_key_todo_resolve().await?;
+            self.user_interface.ask_key_todo_delete().await?;
 
             match self.user_interface.parse_user_option().await {
                 Ok(key) => {
                     if self
-                        .todo_is_found(key, "\n✅ TODO resolvido com sucesso! ✅\n")
+                        .todo_is_found(key, "\n❌ O TODO foi movido para a lixeira! ❌\n")
                         .await?
+                        .is_some()
                     {
-                        self.todo_storage.resolve_one_todo(key);
-                        self.todo_storage
-                            .parse_map_write_file("todo_list.txt")
-                            .await?;
+                        self.stores.todos.lock().await.remove(key);
+                        self.autosave.touch();
                         return Ok(());
                     }
                 }
@@ -172,166 +357,1915 @@ impl TodoCli {
         Ok(())
     }
 
-    async fn todo_is_found(&mut self, key: u32, feedback: &str) -> Result<bool, TerminalError> {
-        let result = self
-            .user_interface
-            .or_not_found(self.todo_storage.get_one_todo(key));
-        match result {
-            Ok(todo) => {
-                self.user_interface.show_todo(&todo, "\n✅ ").await?;
-                self.user_interface.write_feedback(feedback).await?;
-                Ok(true)
-            }
+    async fn undo_todos(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        self.user_interface.ask_undo_steps().await?;
+
+        match self.user_interface.parse_user_option().await {
+            Ok(steps) => match self.stores.todos.lock().await.undo(steps) {
+                0 => self.user_interface.show_error(TerminalError::NotFound(
+                    "Não há operações para desfazer".to_string(),
+                )),
+                undone => {
+                    self.autosave.touch();
+                    self.user_interface
+                        .write_feedback(&format!("\n↩️ {undone} operação(ões) desfeita(s)! ↩️\n"))
+                        .await?;
+                }
+            },
             Err(error) => {
                 self.user_interface.clean()?;
-                self.user_interface.show_error(error);
-                Ok(false)
+                self.user_interface.show_error(error)
             }
         }
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::todo::*;
-    use crate::todo::mocks::_Factori_Builder_Todo;
-    use crate::{terminal::MockUserInterface, todos::MockTodoStorage};
-    use std::collections::BTreeMap;
+    async fn redo_todos(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        self.user_interface.ask_redo_steps().await?;
 
-    fn create_mocks() -> (MockUserInterface, MockTodoStorage) {
-        let mut list = BTreeMap::<u32, Todo>::new();
-        list.entry(1).or_insert(factori::create!(Todo));
+        match self.user_interface.parse_user_option().await {
+            Ok(steps) => match self.stores.todos.lock().await.redo(steps) {
+                0 => self.user_interface.show_error(TerminalError::NotFound(
+                    "Não há operações para refazer".to_string(),
+                )),
+                redone => {
+                    self.autosave.touch();
+                    self.user_interface
+                        .write_feedback(&format!("\n↪️ {redone} operação(ões) refeita(s)! ↪️\n"))
+                        .await?;
+                }
+            },
+            Err(error) => {
+                self.user_interface.clean()?;
+                self.user_interface.show_error(error)
+            }
+        }
+        Ok(())
+    }
 
-        let mut mock_user_interface = MockUserInterface::new();
-        mock_user_interface.expect_clean().returning(|| Ok(()));
-        mock_user_interface
-            .expect_ask_key_todo_update()            
-            .return_once(move || Ok(()));
-        mock_user_interface
-            .expect_parse_user_option()
-            .returning(|| Ok(1));
-        mock_user_interface
-            .expect_write_feedback()
-            .returning(|_| Ok(()));
-        mock_user_interface
-            .expect_or_not_found()
-            .returning(|_| Ok(Todo::new("boo".to_string())));
-        mock_user_interface
-            .expect_get_key_todo_resolve()
-            .returning(|| Ok(()));
-        mock_user_interface
-            .expect_ask_key_todo_delete()
-            .return_once(|| Ok(()));
+    async fn show_todos_at(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        let moment = match self
+            .user_interface
+            .ask_for_date("\nDe qual data deseja ver a lista? 🕰️\n")
+            .await
+        {
+            Ok(moment) => moment,
+            Err(error) => {
+                self.user_interface.show_error(error);
+                return Ok(());
+            }
+        };
 
-        let mut mock_storage = MockTodoStorage::new();
-        mock_storage
-            .expect_parse_map_write_file()
-            .returning(|_| Ok(()));
-        mock_storage.expect_insert_todo().return_once(|_| ());
-        mock_storage.expect_get_collection().return_const(list);
-        mock_storage
-            .expect_update()
-            .withf(|key, todo| key == &1 && todo.message == "boo")
-            .return_once(|_, _| true);
-        mock_storage.expect_is_empty().returning(|| 1);
-        mock_storage
-            .expect_get_one_todo()
-            .returning(|_| Some(Todo::new("boo".to_string())));
-        mock_storage.expect_resolve_one_todo().return_once(|_| true);
-        mock_storage.expect_remove().return_once(|_| ());
-        mock_storage.expect_parse_file_for_todos().withf(|path| path == "todo_list.txt").return_once(|_| Ok(()));
+        let todos = self
+            .stores
+            .todos
+            .lock()
+            .await
+            .todos_at(&self.config.file, moment)
+            .await?;
+        if todos.is_empty() {
+            self.user_interface.show_error(TerminalError::NotFound(
+                "A sua coleção de TODOs estava vazia nessa data".to_string(),
+            ));
+            return Ok(());
+        }
 
-        (mock_user_interface, mock_storage)
+        self.user_interface
+            .write_styled(
+                &format!(
+                    "\nOs seus TODO's em {}: 📖\n\n",
+                    moment.with_timezone(&Local).format("%d/%m/%Y %H:%M")
+                ),
+                Style::new().blue().bold(),
+            )
+            .await?;
+        for (key, todo) in &todos {
+            self.user_interface
+                .show_todo(todo, format!("{key}: ").as_str())
+                .await?;
+        }
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn test_cli_run() {
-        let (mut mock_user_interface, mock_storage) = create_mocks();
-       
-        mock_user_interface
-            .expect_show_options()           
-            .returning(|| Ok(()));
-        mock_user_interface
-            .expect_write_styled()           
-            .returning(|_,_| Ok(()));
-        
-        mock_user_interface
-            .expect_get_user_command()       
-            .return_once(|| Ok(UserCommand::Exit));
-        mock_user_interface
-            .expect_finish_todo()       
-            .returning(|| Ok(())); 
-
+    async fn compare_todos(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        let mut moments = Vec::new();
+        for question in [
+            "\nDigite a data INICIAL da comparação: 🕰️\n",
+            "\nDigite a data FINAL da comparação: 🕰️\n",
+        ] {
+            match self.user_interface.ask_for_date(question).await {
+                Ok(moment) => moments.push(moment),
+                Err(error) => {
+                    self.user_interface.show_error(error);
+                    return Ok(());
+                }
+            }
+        }
 
-        let mut cli = TodoCli {
-            user_interface: Box::new(mock_user_interface),
-            todo_storage: Box::new(mock_storage),
-        };
+        let before = self
+            .stores
+            .todos
+            .lock()
+            .await
+            .todos_at(&self.config.file, moments[0])
+            .await?;
+        let after = self
+            .stores
+            .todos
+            .lock()
+            .await
+            .todos_at(&self.config.file, moments[1])
+            .await?;
+        let changes = timeline::diff(&before, &after);
+        if changes.is_empty() {
+            self.user_interface.show_error(TerminalError::NotFound(
+                "Nenhuma mudança entre as datas informadas".to_string(),
+            ));
+            return Ok(());
+        }
 
-        cli.run().await.unwrap();        
+        self.user_interface
+            .write_styled(
+                "\nMudanças entre as datas: 🔍\n\n",
+                Style::new().blue().bold(),
+            )
+            .await?;
+        for change in &changes {
+            self.user_interface.show_change(change).await?;
+        }
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn add_todo_and_verify_if_exist_with_show_todo() {
-        let (mut mock_user_interface, mock_storage) = create_mocks();
-
-        mock_user_interface
-            .expect_ask_for_new_todo()
-            .times(1)
-            .return_once(|| Ok(factori::create!(Todo)));
-        mock_user_interface
-            .expect_show_todo()            
-            .withf(|todo, _| todo.message == "foo")
-            .returning(|_, _| Ok(()));
+    async fn manage_backups(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        let backups = self
+            .stores
+            .todos
+            .lock()
+            .await
+            .list_backups(&self.config.file)
+            .await?;
+        if backups.is_empty() {
+            self.user_interface.show_error(TerminalError::NotFound(
+                "Nenhum backup encontrado".to_string(),
+            ));
+            return Ok(());
+        }
 
-        let mut cli = TodoCli {
-            user_interface: Box::new(mock_user_interface),
-            todo_storage: Box::new(mock_storage),
+        self.user_interface.show_backups(&backups).await?;
+        self.user_interface.ask_key_backup().await?;
+        let backup = match self.user_interface.parse_user_option().await {
+            Ok(position) => match backups.get((position as usize).wrapping_sub(1)) {
+                Some(backup) => backup.clone(),
+                None => {
+                    self.user_interface.show_error(TerminalError::NotFound(
+                        "❗ O backup consultado não existe ❗".to_string(),
+                    ));
+                    return Ok(());
+                }
+            },
+            Err(error) => {
+                self.user_interface.clean()?;
+                self.user_interface.show_error(error);
+                return Ok(());
+            }
         };
 
-        cli.add_todo().await.unwrap();
-        cli.show_all_todos(true).await.unwrap();
+        let todos = self
+            .stores
+            .todos
+            .lock()
+            .await
+            .read_backup(&self.config.file, &backup)
+            .await?;
+        self.user_interface
+            .write_styled(
+                &format!(
+                    "\nConteúdo do backup de {}: 📖\n\n",
+                    backup.at.with_timezone(&Local).format("%d/%m/%Y %H:%M:%S")
+                ),
+                Style::new().blue().bold(),
+            )
+            .await?;
+        for (key, todo) in &todos {
+            self.user_interface
+                .show_todo(todo, format!("{key}: ").as_str())
+                .await?;
+        }
+
+        if self
+            .user_interface
+            .confirm("\nDeseja RESTAURAR este backup? (s/n)\n")
+            .await?
+        {
+            let restored = self.stores.todos.lock().await.restore(todos);
+            self.autosave.touch();
+            self.user_interface
+                .write_feedback(&format!(
+                    "\n♻️ Backup restaurado! {restored} TODO(s) alterado(s) ♻️\n"
+                ))
+                .await?;
+        }
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn test_update_todo() {
-        let (mut mock_user_interface, mock_storage) = create_mocks();
-        mock_user_interface
-            .expect_ask_for_new_todo()
-            .times(1)
-            .return_once(|| Ok(Todo::new("boo".to_string())));
-        mock_user_interface
-            .expect_show_todo()
-            .withf(|todo, _| todo.message == "foo")
-            .return_once(|_, _| Ok(()));
-        mock_user_interface
-            .expect_show_todo()
-            .withf(|todo, _| todo.message == "boo")
-            .return_once(|_, _| Ok(()));
+    async fn unlock_storage(&mut self) -> Result<(), TerminalError> {
+        if !self
+            .stores
+            .crypto
+            .lock()
+            .await
+            .is_encrypted(&self.config.file)
+            .await
+        {
+            return Ok(());
+        }
 
-        let mut cli = TodoCli {
-            user_interface: Box::new(mock_user_interface),
-            todo_storage: Box::new(mock_storage),
-        };
-        cli.update_todo().await.unwrap();
+        let mut attempts = 0;
+        loop {
+            let passphrase = self
+                .user_interface
+                .ask_passphrase("\n🔒 Digite a senha do arquivo de TODOs:\n")
+                .await?;
+            attempts += 1;
+            match self
+                .stores
+                .crypto
+                .lock()
+                .await
+                .unlock(&self.config.file, &passphrase)
+                .await
+            {
+                Err(error) if error.is_wrong_passphrase() && attempts < 3 => {
+                    self.user_interface.show_error(error.into())
+                }
+                result => return Ok(result?),
+            }
+        }
     }
 
-    #[tokio::test]
-    async fn test_resolve_and_delete_todo() {
-        let (mut mock_user_interface, mock_storage) = create_mocks();
+    async fn change_passphrase(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        let passphrase = self
+            .user_interface
+            .ask_passphrase("\n🔑 Digite a NOVA senha do arquivo de TODOs:\n")
+            .await?;
+        let confirmation = self
+            .user_interface
+            .ask_passphrase("\n🔑 Repita a nova senha:\n")
+            .await?;
 
-        mock_user_interface
-            .expect_show_todo()
-            .withf(|todo, _| todo.message.contains("oo"))
-            .returning(|_, _| Ok(()));
+        if passphrase.is_empty() || passphrase != confirmation {
+            self.user_interface.show_error(TerminalError::NotFound(
+                "❗ As senhas não conferem ou estão vazias ❗".to_string(),
+            ));
+            return Ok(());
+        }
+
+        self.stores
+            .crypto
+            .lock()
+            .await
+            .change_passphrase(&self.config.file, &passphrase)
+            .await?;
+        self.user_interface
+            .write_feedback("\n🔒 Senha definida! O arquivo de TODOs está criptografado 🔒\n")
+            .await?;
+        Ok(())
+    }
 
-        let mut cli = TodoCli {
-            user_interface: Box::new(mock_user_interface),
-            todo_storage: Box::new(mock_storage),
+    async fn git_history(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        let commits = match self
+            .stores
+            .git
+            .lock()
+            .await
+            .git_log(&self.config.file)
+            .await
+        {
+            Ok(commits) if commits.is_empty() => {
+                self.user_interface.show_error(TerminalError::NotFound(
+                    "Nenhuma versão encontrada".to_string(),
+                ));
+                return Ok(());
+            }
+            Ok(commits) => commits,
+            Err(error) => {
+                self.user_interface.show_error(error.into());
+                return Ok(());
+            }
         };
 
-        cli.resolve_todo().await.unwrap();
+        self.user_interface.show_commits(&commits).await?;
+        self.user_interface.ask_key_commit().await?;
+        let commit = match self.user_interface.parse_user_option().await {
+            Ok(position) => match commits.get((position as usize).wrapping_sub(1)) {
+                Some(commit) => commit,
+                None => {
+                    self.user_interface.show_error(TerminalError::NotFound(
+                        "❗ A versão consultada não existe ❗".to_string(),
+                    ));
+                    return Ok(());
+                }
+            },
+            Err(error) => {
+                self.user_interface.clean()?;
+                self.user_interface.show_error(error);
+                return Ok(());
+            }
+        };
+
+        if !self
+            .user_interface
+            .confirm("\nDeseja REVERTER a lista para esta versão? (s/n)\n")
+            .await?
+        {
+            return Ok(());
+        }
+        match self
+            .stores
+            .git
+            .lock()
+            .await
+            .revert_to(&self.config.file, commit)
+            .await
+        {
+            Ok(restored) => {
+                self.autosave.touch();
+                self.user_interface
+                    .write_feedback(&format!(
+                        "\n⏪ Versão {} restaurada! {restored} TODO(s) alterado(s) ⏪\n",
+                        commit.id
+                    ))
+                    .await?;
+            }
+            Err(error) => self.user_interface.show_error(error.into()),
+        }
+        Ok(())
+    }
+
+    async fn git_sync(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        match self
+            .stores
+            .git
+            .lock()
+            .await
+            .git_sync(&self.config.file)
+            .await
+        {
+            Ok(()) => {
+                self.user_interface
+                    .write_feedback("\n🔄 Lista sincronizada com o remoto! 🔄\n")
+                    .await?
+            }
+            Err(error) => self.user_interface.show_error(error.into()),
+        }
+        Ok(())
+    }
+
+    async fn sync_todos(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        let other = self.user_interface.ask_sync_file().await?;
+        if other.is_empty() || other == self.config.file {
+            self.user_interface
+                .show_error(TerminalError::InvalidArgument(
+                    "❗ Informe o caminho de OUTRA cópia da lista ❗".to_string(),
+                ));
+            return Ok(());
+        }
+
+        let mut merge = match self
+            .stores
+            .sync
+            .lock()
+            .await
+            .plan_sync(&self.config.file, &other)
+            .await
+        {
+            Ok(merge) => merge,
+            Err(error) => {
+                self.user_interface.show_error(error.into());
+                return Ok(());
+            }
+        };
+        for conflict in std::mem::take(&mut merge.conflicts) {
+            let resolution = self.user_interface.ask_conflict(&conflict).await?;
+            merge.resolve(conflict, resolution);
+        }
+
+        match self
+            .stores
+            .sync
+            .lock()
+            .await
+            .apply_sync(&self.config.file, &other, merge)
+            .await
+        {
+            Ok(changed) => {
+                self.user_interface
+                    .write_feedback(&format!(
+                        "\n🔀 Listas sincronizadas! {changed} TODO(s) alterado(s) aqui 🔀\n"
+                    ))
+                    .await?
+            }
+            Err(error) => self.user_interface.show_error(error.into()),
+        }
+        Ok(())
+    }
+
+    async fn renumber_todos(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        let renumbered = self.stores.todos.lock().await.renumber();
+        match renumbered {
+            0 => self.user_interface.show_error(TerminalError::NotFound(
+                "Os TODOs já estão numerados em sequência".to_string(),
+            )),
+            renumbered => {
+                self.autosave.touch();
+                self.user_interface
+                    .write_feedback(&format!("\n🔢 {renumbered} TODO(s) renumerado(s)! 🔢\n"))
+                    .await?;
+                self.show_all_todos(true).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn manage_trash(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        let trashed = self.stores.todos.lock().await.trashed();
+        if trashed.is_empty() {
+            self.user_interface
+                .show_error(TerminalError::NotFound("A lixeira está vazia".to_string()));
+            return Ok(());
+        }
+
+        self.user_interface.show_trash(&trashed).await?;
+        self.user_interface.ask_key_trash().await?;
+        match self.user_interface.parse_user_option().await {
+            Ok(0) => {
+                if self
+                    .user_interface
+                    .confirm("\nDeseja ESVAZIAR a lixeira? Os TODOs serão apagados de vez (s/n)\n")
+                    .await?
+                {
+                    let purged = self.stores.todos.lock().await.empty_trash();
+                    self.autosave.touch();
+                    self.user_interface
+                        .write_feedback(&format!(
+                            "\n🗑️ Lixeira esvaziada! {purged} TODO(s) apagado(s) de vez 🗑️\n"
+                        ))
+                        .await?;
+                }
+            }
+            Ok(position) => {
+                let restored = match trashed.get(position as usize - 1) {
+                    Some(item) => self
+                        .stores
+                        .todos
+                        .lock()
+                        .await
+                        .restore_from_trash(&item.todo.id),
+                    None => None,
+                };
+                match restored {
+                    Some(key) => {
+                        self.autosave.touch();
+                        self.user_interface
+                            .write_feedback(&format!("\n♻️ TODO restaurado na chave {key}! ♻️\n"))
+                            .await?;
+                    }
+                    None => self.user_interface.show_error(TerminalError::NotFound(
+                        "❗ O item consultado não está na lixeira ❗".to_string(),
+                    )),
+                }
+            }
+            Err(error) => {
+                self.user_interface.clean()?;
+                self.user_interface.show_error(error);
+            }
+        }
+        Ok(())
+    }
+
+    async fn search_todos(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        let query = self.user_interface.ask_search().await?;
+        let search = match Search::parse(&query) {
+            Ok(search) => search,
+            Err(reason) => {
+                self.user_interface
+                    .show_error(TerminalError::InvalidArgument(format!("❗ {reason} ❗")));
+                return Ok(());
+            }
+        };
+
+        let hits = self.stores.todos.lock().await.search(&search);
+        if hits.is_empty() {
+            self.user_interface.show_error(TerminalError::NotFound(
+                "Nenhum TODO encontrado para a busca".to_string(),
+            ));
+            return Ok(());
+        }
+        self.user_interface
+            .write_styled(
+                &format!("\n🔎 {} resultado(s) para '{query}':\n\n", hits.len()),
+                Style::new().blue().bold(),
+            )
+            .await?;
+        self.user_interface.highlight(Some(search));
+        for hit in &hits {
+            self.user_interface
+                .show_todo(&hit.todo, format!("{}: ", hit.key).as_str())
+                .await?;
+        }
+        self.user_interface.highlight(None);
+        Ok(())
+    }
+
+    async fn show_smart_list(&mut self, list: SmartList) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        let query = Query::smart(list, self.config.calendar);
+        let sort = self.stores.views.lock().await.sort_for(query.source());
+        self.user_interface
+            .write_styled(
+                &format!("\n{} (ordem: {sort}): 📅\n\n", list.title()),
+                Style::new().blue().bold(),
+            )
+            .await?;
+        self.show_page(&query, &sort, true).await
+    }
+
+    async fn open_view(&mut self, name: &str) -> Result<(), TerminalError> {
+        let name = View::normalize(name);
+        let view = self
+            .stores
+            .views
+            .lock()
+            .await
+            .views()
+            .into_iter()
+            .find(|view| view.name == name);
+        let Some(view) = view else {
+            self.user_interface
+                .show_error(TerminalError::NotFound(format!(
+                    "A visão '{name}' não foi encontrada"
+                )));
+            return Ok(());
+        };
+        let query = match Query::parse(&view.query) {
+            Ok(query) => query,
+            Err(error) => {
+                self.user_interface.show_error(error);
+                return Ok(());
+            }
+        };
+
+        self.user_interface
+            .write_styled(
+                &format!("\n👓 Visão '{}': {}\n\n", view.name, view.query),
+                Style::new().blue().bold(),
+            )
+            .await?;
+        self.show_page(&query, &view.sort, false).await
+    }
+
+    async fn manage_views(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        let views = self.stores.views.lock().await.views();
+        self.user_interface.show_views(&views).await?;
+        let name = View::normalize(&self.user_interface.ask_view_name().await?);
+        if name.is_empty() {
+            return Ok(());
+        }
+
+        let query = self.user_interface.ask_view_query().await?;
+        if query.is_empty() {
+            if self.stores.views.lock().await.delete_view(&name) {
+                self.autosave.touch();
+                self.user_interface
+                    .write_feedback(&format!("\n❌ Visão '{name}' excluída! ❌\n"))
+                    .await?;
+            } else {
+                self.user_interface
+                    .show_error(TerminalError::NotFound(format!(
+                        "A visão '{name}' não foi encontrada"
+                    )));
+            }
+            return Ok(());
+        }
+        if let Err(error) = Query::parse(&query) {
+            self.user_interface.show_error(error);
+            return Ok(());
+        }
+        let sort = match self.user_interface.ask_sort(&Sort::default()).await {
+            Ok(sort) => sort,
+            Err(error) => {
+                self.user_interface.show_error(error);
+                return Ok(());
+            }
+        };
+        self.stores
+            .views
+            .lock()
+            .await
+            .save_view(View::new(&name, &query).with_sort(sort));
+        self.autosave.touch();
+        self.user_interface
+            .write_feedback(&format!(
+                "\n✅ Visão '{name}' salva! Digite @{name} no menu para abrir ✅\n"
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn reorder_todos(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        while self.check_list_is_empty(&*self.stores.todos.lock().await) {
+            self.show_page(&Query::default(), &Sort::default(), true)
+                .await?;
+            let (key, arrange) = match self.user_interface.ask_arrange().await {
+                Ok(Some(choice)) => choice,
+                Ok(None) => return Ok(()),
+                Err(error) => {
+                    self.user_interface.clean()?;
+                    self.user_interface.show_error(error);
+                    continue;
+                }
+            };
+
+            let changed = match arrange {
+                Arrange::Move(to) => self.stores.todos.lock().await.move_todo(key, to),
+                Arrange::Pin => self.stores.todos.lock().await.toggle_pin(key).is_some(),
+            };
+            self.user_interface.clean()?;
+            if changed {
+                self.autosave.touch();
+            } else {
+                self.user_interface
+                    .show_error(TerminalError::NotFound(format!(
+                        "O TODO {key} não existe ou já está nessa posição"
+                    )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn show_stats(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        let stats = {
+            let storage = self.stores.todos.lock().await;
+            if !self.check_list_is_empty(&*storage) {
+                return Ok(());
+            }
+            storage.stats(Utc::now())
+        };
+        self.user_interface.show_stats(&stats).await
+    }
+
+    async fn export_report(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        let mut days = Vec::new();
+        for question in [
+            "\nDigite a data INICIAL do relatório: 📅\n",
+            "\nDigite a data FINAL do relatório: 📅\n",
+        ] {
+            match self.user_interface.ask_for_date(question).await {
+                Ok(moment) => days.push(moment.with_timezone(&Local).date_naive()),
+                Err(error) => {
+                    self.user_interface.show_error(error);
+                    return Ok(());
+                }
+            }
+        }
+        let options = match self.user_interface.ask_report_options().await {
+            Ok(options) => options,
+            Err(error) => {
+                self.user_interface.show_error(error);
+                return Ok(());
+            }
+        };
+
+        let report = self.stores.todos.lock().await.report(
+            days[0].min(days[1]),
+            days[0].max(days[1]),
+            options.grouping,
+        );
+        if report.resolved == 0 {
+            self.user_interface.show_error(TerminalError::NotFound(
+                "Nenhum TODO foi resolvido nesse período".to_string(),
+            ));
+            return Ok(());
+        }
+        if self
+            .stores
+            .crypto
+            .lock()
+            .await
+            .is_encrypted(&self.config.file)
+            .await
+            && !self
+                .user_interface
+                .confirm("\n⚠️ A lista é criptografada, mas o relatório será salvo SEM criptografia. Deseja continuar? (s/n)\n")
+                .await?
+        {
+            return Ok(());
+        }
+        let path = report.path(&self.config.file, options.format);
+        let rendered = report.render(options.format);
+        match tokio::fs::write(&path, &rendered).await {
+            Ok(()) => self.user_interface.show_report(&rendered, &path).await,
+            Err(error) => {
+                self.user_interface
+                    .show_error(StorageError::io(&path, error).into());
+                Ok(())
+            }
+        }
+    }
+
+    async fn follow_file(&mut self) -> Result<(), TerminalError> {
+        if let Some(reload) = self
+            .stores
+            .todos
+            .lock()
+            .await
+            .reload_if_changed(&self.config.file)
+            .await?
+        {
+            self.user_interface.show_reload(&reload).await?;
+        }
+        Ok(())
+    }
+
+    fn check_list_is_empty(&self, list: &dyn TodoStorage) -> bool {
+        if list.is_empty() < 1 {
+            self.user_interface.show_error(TerminalError::NotFound(
+                "A sua coleção de TODOs esta vazia".to_string(),
+            ));
+            return false;
+        }
+        true
+    }
+
+    async fn show_all_todos(&mut self, show_keys: bool) -> Result<(), TerminalError> {
+        let sort = self.stores.views.lock().await.sort_for("");
+        self.show_page(&Query::default(), &sort, show_keys).await
+    }
+
+    async fn show_page(
+        &mut self,
+        query: &Query,
+        sort: &Sort,
+        show_keys: bool,
+    ) -> Result<(), TerminalError> {
+        let size = self.user_interface.page_size();
+        let mut number = 0;
+        loop {
+            let page = self
+                .stores
+                .todos
+                .lock()
+                .await
+                .page(query, sort, number, size);
+            self.user_interface.show_table(&page, show_keys).await?;
+            if page.total <= page.size {
+                return Ok(());
+            }
+
+            self.user_interface.show_page_footer(&page).await?;
+            match self.user_interface.ask_page_turn(&page).await? {
+                PageTurn::Next if page.has_next() => number += 1,
+                PageTurn::Previous if number > 0 => number -= 1,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    async fn resolve_todo(&mut self) -> Result<(), TerminalError> {
+        self.user_interface.clean()?;
+        while self.check_list_is_empty(&*self.stores.todos.lock().await) {
+            self.show_all_todos(true).await?;
+            self.user_interface.get_key_todo_resolve().await?;
+
+            match self.user_interface.parse_user_option().await {
+                Ok(key) => {
+                    if self
+                        .todo_is_found(key, "\n✅ TODO resolvido com sucesso! ✅\n")
+                        .await?
+                        .is_some()
+                    {
+                        self.stores.todos.lock().await.resolve_one_todo(key);
+                        self.autosave.touch();
+                        return Ok(());
+                    }
+                }
+                Err(error) => {
+                    self.user_interface.clean()?;
+                    self.user_interface.show_error(error)
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn todo_is_found(
+        &mut self,
+        key: u32,
+        feedback: &str,
+    ) -> Result<Option<Todo>, TerminalError> {
+        let result = self
+            .user_interface
+            .or_not_found(self.stores.todos.lock().await.get_one_todo(key));
+        match result {
+            Ok(todo) => {
+                self.user_interface.show_todo(&todo, "\n✅ ").await?;
+                self.user_interface.write_feedback(feedback).await?;
+                Ok(Some(todo))
+            }
+            Err(error) => {
+                self.user_interface.clean()?;
+                self.user_interface.show_error(error);
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::*;
+    use crate::backup::Backup;
+    use crate::error::{Cause, StorageError};
+    use crate::git::Commit;
+    use crate::index::Page;
+    use crate::recovery::LoadReport;
+    use crate::sync::{Conflict, Merge, Resolution};
+    use crate::trash::TrashItem;
+    use crate::sort::Move;
+    use crate::report::{Grouping, Options, Report};
+    use crate::command::Subcommand;
+    use crate::smart::Calendar;
+    use crate::stats::Stats;
+    use chrono::TimeZone;
+    use crate::watch::Reload;
+    use crate::todo::mocks::_Factori_Builder_Todo;
+    use crate::crypto::MockCryptoStorage;
+    use crate::git::MockGitStorage;
+    use crate::sync::MockSyncStorage;
+    use crate::views::MockViewStorage;
+    use crate::{terminal::MockUserInterface, todos::MockTodoStorage};
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+    use std::collections::BTreeMap;
+
+    const PAGE_SIZE: usize = 50;
+
+    #[derive(Default)]
+    struct Mocks {
+        todos: MockTodoStorage,
+        views: MockViewStorage,
+        crypto: MockCryptoStorage,
+        git: MockGitStorage,
+        sync: MockSyncStorage,
+    }
+
+    impl Mocks {
+        fn cli(self, ui: MockUserInterface, config: Config) -> TodoCli {
+            let stores = Stores {
+                todos: Arc::new(Mutex::new(self.todos)),
+                views: Arc::new(Mutex::new(self.views)),
+                crypto: Arc::new(Mutex::new(self.crypto)),
+                git: Arc::new(Mutex::new(self.git)),
+                sync: Arc::new(Mutex::new(self.sync)),
+            };
+            TodoCli::with_stores(ui, stores, config)
+        }
+    }
+
+    fn create_mocks() -> (MockUserInterface, Mocks) {
+        let mut list = BTreeMap::<u32, Todo>::new();
+        list.entry(1).or_insert(factori::create!(Todo));
+
+        let mut mock_user_interface = MockUserInterface::new();
+        mock_user_interface.expect_clean().returning(|| Ok(()));
+        mock_user_interface
+            .expect_page_size()
+            .return_const(PAGE_SIZE);
+        mock_user_interface
+            .expect_ask_key_todo_update()            
+            .return_once(move || Ok(()));
+        mock_user_interface
+            .expect_parse_user_option()
+            .returning(|| Ok(1));
+        mock_user_interface
+            .expect_write_feedback()
+            .returning(|_| Ok(()));
+        mock_user_interface
+            .expect_or_not_found()
+            .returning(|_| Ok(Todo::new("boo".to_string())));
+        mock_user_interface
+            .expect_get_key_todo_resolve()
+            .returning(|| Ok(()));
+        mock_user_interface
+            .expect_ask_key_todo_delete()
+            .return_once(|| Ok(()));
+
+        let mut mocks = Mocks::default();
+        mocks
+            .todos
+            .expect_parse_map_write_file()
+            .returning(|_| Ok(()));
+        mocks
+            .todos
+            .expect_reload_if_changed()
+            .returning(|_| Ok(None));
+        mocks.todos.expect_insert_todo().return_once(|_| 2);
+        mocks
+            .todos
+            .expect_page()
+            .returning(move |_, _, number, size| Page {
+                todos: list.clone().into_iter().collect(),
+                number,
+                size,
+                total: list.len(),
+            });
+        mocks.views.expect_sort_for().returning(|_| Sort::default());
+        mocks
+            .todos
+            .expect_update()
+            .withf(|key, todo| key == &1 && todo.message == "boo")
+            .return_once(|_, _| true);
+        mocks.todos.expect_is_empty().returning(|| 1);
+        mocks
+            .todos
+            .expect_get_one_todo()
+            .returning(|_| Some(Todo::new("boo".to_string())));
+        mocks.todos.expect_resolve_one_todo().return_once(|_| true);
+        mocks.todos.expect_remove().return_once(|_| ());
+        mocks.crypto.expect_is_encrypted().returning(|_| false);
+        mocks
+            .todos
+            .expect_undo()
+            .withf(|steps| steps == &1)
+            .return_once(|_| 1);
+        mocks.todos.expect_redo().return_once(|_| 0);
+        mocks
+            .todos
+            .expect_parse_file_for_todos()
+            .withf(|path| path == "todo_list.txt")
+            .return_once(|_| Ok(LoadReport::default()));
+
+        (mock_user_interface, mocks)
+    }
+
+    #[tokio::test]
+    async fn test_cli_run() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        mocks.views.expect_views().returning(Vec::new);
+       
+        mock_user_interface
+            .expect_show_options()           
+            .returning(|_| Ok(()));
+        mock_user_interface
+            .expect_write_styled()           
+            .returning(|_,_| Ok(()));
+        
+        mock_user_interface
+            .expect_get_user_command()       
+            .return_once(|| Ok(UserCommand::Exit));
+        mock_user_interface
+            .expect_show_load_report()
+            .return_once(|_| Ok(()));
+        mock_user_interface
+            .expect_finish_todo()       
+            .returning(|| Ok(())); 
+
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+
+        cli.run().await.unwrap();        
+    }
+
+    #[tokio::test]
+    async fn add_todo_and_verify_if_exist_with_show_todo() {
+        let (mut mock_user_interface, mocks) = create_mocks();
+
+        mock_user_interface
+            .expect_ask_for_new_todo()
+            .times(1)
+            .return_once(|| Ok(factori::create!(Todo)));
+        mock_user_interface
+            .expect_show_todo()            
+            .withf(|todo, _| todo.message == "foo")
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_show_table()
+            .withf(|page, show_keys| page.todos[0].1.message == "foo" && *show_keys)
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+
+        cli.add_todo().await.unwrap();
+        cli.show_all_todos(true).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_show_todos_pages_through_filter() {
+        let mut mock_user_interface = MockUserInterface::new();
+        mock_user_interface.expect_clean().returning(|| Ok(()));
+        mock_user_interface
+            .expect_ask_query()
+            .return_once(|| Query::parse("tag:casa -\"louça\""));
+        mock_user_interface
+            .expect_ask_sort()
+            .withf(|current| current.is_manual())
+            .return_once(|current| Sort::parse("prazo", *current));
+        mock_user_interface
+            .expect_write_styled()
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_page_size()
+            .return_const(PAGE_SIZE);
+        let mut sequence = mockall::Sequence::new();
+        for (number, turn) in [
+            (0, PageTurn::Next),
+            (1, PageTurn::Next),
+            (2, PageTurn::Previous),
+            (1, PageTurn::Stop),
+        ] {
+            mock_user_interface
+                .expect_show_table()
+                .withf(move |page, show_keys| page.number == number && !show_keys)
+                .times(1)
+                .in_sequence(&mut sequence)
+                .returning(|_, _| Ok(()));
+            mock_user_interface
+                .expect_show_page_footer()
+                .times(1)
+                .in_sequence(&mut sequence)
+                .returning(|_| Ok(()));
+            mock_user_interface
+                .expect_ask_page_turn()
+                .times(1)
+                .in_sequence(&mut sequence)
+                .return_once(move |_| Ok(turn));
+        }
+
+        let mut mocks = Mocks::default();
+        mocks
+            .todos
+            .expect_page()
+            .withf(|query, sort, _, size| {
+                query == &Query::parse("tag:casa -\"louça\"").unwrap()
+                    && sort.to_string() == "prazo ↑"
+                    && size == &PAGE_SIZE
+            })
+            .times(4)
+            .returning(|_, _, number, size| Page {
+                todos: (number * size..(number * size + size).min(120))
+                    .map(|key| (key as u32 + 1, Todo::new("limpar #casa".to_string())))
+                    .collect(),
+                number,
+                size,
+                total: 120,
+            });
+        mocks
+            .views
+            .expect_sort_for()
+            .withf(|list| list == "tag:casa -\"louça\"")
+            .return_once(|_| Sort::default());
+        mocks
+            .views
+            .expect_remember_sort()
+            .withf(|list, sort| list == "tag:casa -\"louça\"" && !sort.is_manual())
+            .times(1)
+            .return_once(|_, _| true);
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+        cli.show_todos().await.unwrap();
+        assert!(cli.autosave.is_dirty());
+    }
+
+    #[tokio::test]
+    async fn test_update_todo() {
+        let (mut mock_user_interface, mocks) = create_mocks();
+        mock_user_interface
+            .expect_ask_for_new_todo()
+            .times(1)
+            .return_once(|| Ok(Todo::new("boo".to_string())));
+        mock_user_interface
+            .expect_show_table()
+            .withf(|page, _| page.todos[0].1.message == "foo")
+            .return_once(|_, _| Ok(()));
+        mock_user_interface
+            .expect_show_todo()
+            .withf(|todo, _| todo.message == "boo")
+            .return_once(|_, _| Ok(()));
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+        cli.update_todo().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_resolve_and_delete_todo() {
+        let (mut mock_user_interface, mocks) = create_mocks();
+
+        mock_user_interface
+            .expect_show_todo()
+            .withf(|todo, _| todo.message.contains("oo"))
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_show_table()
+            .times(2)
+            .returning(|_, _| Ok(()));
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+
+        cli.resolve_todo().await.unwrap();
         cli.delete_todo().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_show_todos_at_and_compare() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        mock_user_interface
+            .expect_ask_for_date()
+            .times(3)
+            .returning(|_| Ok(chrono::Utc::now()));
+        mock_user_interface
+            .expect_write_styled()
+            .times(2)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_show_todo()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_show_change()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut past = BTreeMap::<u32, Todo>::new();
+        past.entry(1).or_insert(factori::create!(Todo));
+        let before = past.clone();
+        mocks
+            .todos
+            .expect_todos_at()
+            .times(1)
+            .return_once(move |_, _| Ok(before));
+        mocks
+            .todos
+            .expect_todos_at()
+            .times(1)
+            .return_once(|_, _| Ok(BTreeMap::new()));
+        mocks
+            .todos
+            .expect_todos_at()
+            .times(1)
+            .return_once(move |_, _| Ok(past));
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+
+        cli.show_todos_at().await.unwrap();
+        cli.compare_todos().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_manage_backups() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        mock_user_interface
+            .expect_show_backups()
+            .withf(|backups| backups.len() == 1)
+            .return_once(|_| Ok(()));
+        mock_user_interface
+            .expect_ask_key_backup()
+            .return_once(|| Ok(()));
+        mock_user_interface
+            .expect_write_styled()
+            .return_once(|_, _| Ok(()));
+        mock_user_interface
+            .expect_show_todo()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_confirm()
+            .return_once(|_| Ok(true));
+
+        mocks.todos.expect_list_backups().return_once(|_| {
+            Ok(vec![Backup {
+                name: "20230118T100000.000.txt".to_string(),
+                at: chrono::Utc::now(),
+            }])
+        });
+        let mut backup = BTreeMap::<u32, Todo>::new();
+        backup.entry(1).or_insert(factori::create!(Todo));
+        mocks
+            .todos
+            .expect_read_backup()
+            .return_once(|_, _| Ok(backup));
+        mocks
+            .todos
+            .expect_restore()
+            .withf(|todos| todos.len() == 1)
+            .return_once(|_| 1);
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+
+        cli.manage_backups().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_manage_trash_restores_item() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        mock_user_interface
+            .expect_show_trash()
+            .withf(|trashed| trashed.len() == 1)
+            .return_once(|_| Ok(()));
+        mock_user_interface
+            .expect_ask_key_trash()
+            .return_once(|| Ok(()));
+
+        mocks.todos.expect_trashed().return_once(|| {
+            vec![TrashItem {
+                key: 3,
+                todo: factori::create!(Todo),
+                at: chrono::Utc::now(),
+            }]
+        });
+        mocks
+            .todos
+            .expect_restore_from_trash()
+            .withf(|id| id == "foo")
+            .return_once(|_| Some(3));
+        mocks.todos.expect_empty_trash().never();
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+
+        cli.manage_trash().await.unwrap();
+        assert!(cli.autosave.is_dirty());
+    }
+
+    #[tokio::test]
+    async fn test_default_view_is_shown_after_greeting() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        mock_user_interface
+            .expect_show_options()
+            .withf(|views| views.len() == 1)
+            .returning(|_| Ok(()));
+        mock_user_interface
+            .expect_write_styled()
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_show_load_report()
+            .return_once(|_| Ok(()));
+        mock_user_interface
+            .expect_show_table()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_get_user_command()
+            .return_once(|| Ok(UserCommand::Exit));
+        mock_user_interface
+            .expect_finish_todo()
+            .returning(|| Ok(()));
+        mocks
+            .views
+            .expect_views()
+            .returning(|| vec![View::new("hoje", "status:open due<=hoje")]);
+
+        let config = Config {
+            view: Some("@Hoje".to_string()),
+            ..Config::default()
+        };
+        let mut cli = mocks.cli(mock_user_interface, config);
+        cli.run().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_smart_list_uses_the_configured_calendar() {
+        let (mut mock_user_interface, _) = create_mocks();
+        mock_user_interface
+            .expect_write_styled()
+            .withf(|message, _| message.contains("Esta semana"))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_user_interface
+            .expect_show_table()
+            .withf(|page, show_keys| page.todos[0].0 == 7 && *show_keys)
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let calendar = Calendar {
+            week_start: chrono::Weekday::Sun,
+            ..Calendar::default()
+        };
+        let mut mocks = Mocks::default();
+        mocks
+            .views
+            .expect_sort_for()
+            .withf(|list| list == "lista:semana")
+            .returning(|_| Sort::default());
+        mocks
+            .todos
+            .expect_page()
+            .withf(move |query, _, _, _| query == &Query::smart(SmartList::Week, calendar))
+            .return_once(|_, _, number, size| Page {
+                todos: vec![(7, Todo::new("revisar PR".to_string()))],
+                number,
+                size,
+                total: 1,
+            });
+
+        let config = Config {
+            calendar,
+            ..Config::default()
+        };
+        let mut cli = mocks.cli(mock_user_interface, config);
+        cli.show_smart_list(SmartList::Week).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_manage_views_saves_and_deletes() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        mock_user_interface
+            .expect_show_views()
+            .returning(|_| Ok(()));
+        mock_user_interface
+            .expect_ask_view_name()
+            .returning(|| Ok("Sprint".to_string()));
+        let mut queries = vec!["", "priority>=urgente", "tag:sprint"];
+        mock_user_interface
+            .expect_ask_view_query()
+            .times(3)
+            .returning(move || Ok(queries.pop().unwrap_or_default().to_string()));
+        mock_user_interface
+            .expect_ask_sort()
+            .times(1)
+            .return_once(|current| Sort::parse("prioridade desc", *current));
+        mock_user_interface
+            .expect_show_error()
+            .times(1)
+            .return_const(());
+        mocks.views.expect_views().returning(Vec::new);
+        mocks
+            .views
+            .expect_save_view()
+            .withf(|view| {
+                view.name == "sprint" && view.query == "tag:sprint" && view.sort.descending
+            })
+            .times(1)
+            .return_once(|_| ());
+        mocks
+            .views
+            .expect_delete_view()
+            .withf(|name| name == "sprint")
+            .times(1)
+            .return_once(|_| true);
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+        for _ in 0..3 {
+            cli.manage_views().await.unwrap();
+        }
+        assert!(cli.autosave.is_dirty());
+    }
+
+    #[tokio::test]
+    async fn test_reorder_todos_moves_and_pins() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        mock_user_interface
+            .expect_show_table()
+            .returning(|_, _| Ok(()));
+        let mut answers = vec![
+            Ok(None),
+            Ok(Some((1, Arrange::Pin))),
+            Err(TerminalError::InvalidArgument(
+                "❗ Ação desconhecida ❗".to_string(),
+            )),
+            Ok(Some((1, Arrange::Move(Move::Top)))),
+        ];
+        mock_user_interface
+            .expect_ask_arrange()
+            .times(4)
+            .returning(move || answers.pop().unwrap());
+        mock_user_interface
+            .expect_show_error()
+            .times(2)
+            .return_const(());
+        mocks
+            .todos
+            .expect_move_todo()
+            .withf(|key, to| key == &1 && *to == Move::Top)
+            .return_once(|_, _| false);
+        mocks
+            .todos
+            .expect_toggle_pin()
+            .withf(|key| key == &1)
+            .return_once(|_| Some(true));
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+        cli.reorder_todos().await.unwrap();
+        assert!(cli.autosave.is_dirty());
+    }
+
+    #[tokio::test]
+    async fn test_show_stats_uses_the_current_time() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        let before = chrono::Utc::now();
+        mocks
+            .todos
+            .expect_stats()
+            .withf(move |now| *now >= before)
+            .return_once(|now| Stats::collect(std::iter::empty(), &[], now));
+        mock_user_interface
+            .expect_show_stats()
+            .withf(|stats| stats.total() == 0 && stats.completions.len() == 30)
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+        cli.show_stats().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_report_writes_the_file() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        let mut moments = vec![
+            chrono::Utc.with_ymd_and_hms(2023, 3, 1, 12, 0, 0).unwrap(),
+            chrono::Utc.with_ymd_and_hms(2023, 3, 7, 12, 0, 0).unwrap(),
+        ];
+        mock_user_interface
+            .expect_ask_for_date()
+            .times(2)
+            .returning(move |_| Ok(moments.pop().unwrap()));
+        mock_user_interface
+            .expect_ask_report_options()
+            .return_once(|| Options::parse("txt"));
+        mocks
+            .todos
+            .expect_report()
+            .withf(|from, to, grouping| {
+                from.to_string() == "2023-03-01"
+                    && to.to_string() == "2023-03-07"
+                    && *grouping == Grouping::Tag
+            })
+            .return_once(|from, to, grouping| {
+                let mut todo = Todo::new("fatura #trabalho".to_string());
+                todo.resolved = true;
+                todo.stamps =
+                    Stamps::at(chrono::Utc.with_ymd_and_hms(2023, 3, 3, 12, 0, 0).unwrap());
+                Report::collect(BTreeMap::from([(1, todo)]).iter(), &[], grouping, from, to)
+            });
+        let path = "test_export_report.txt.relatorio-2023-03-01-2023-03-07.txt";
+        mock_user_interface
+            .expect_show_report()
+            .withf(move |report, shown| report.contains("  * fatura #trabalho") && shown == path)
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let config = Config {
+            file: "test_export_report.txt".to_string(),
+            ..Config::default()
+        };
+        let mut cli = mocks.cli(mock_user_interface, config);
+        cli.export_report().await.unwrap();
+        let written = tokio::fs::read_to_string(path).await.unwrap();
+        tokio::fs::remove_file(path).await.unwrap();
+        assert!(written.starts_with("TODOs resolvidos de 01/03/2023 a 07/03/2023\n"));
+    }
+
+    #[tokio::test]
+    async fn test_export_report_asks_before_writing_plaintext_from_an_encrypted_store() {
+        let mut mock_user_interface = MockUserInterface::new();
+        mock_user_interface.expect_clean().returning(|| Ok(()));
+        mock_user_interface
+            .expect_ask_for_date()
+            .times(2)
+            .returning(|_| Ok(chrono::Utc::now()));
+        mock_user_interface
+            .expect_ask_report_options()
+            .return_once(|| Options::parse("md"));
+        mock_user_interface
+            .expect_confirm()
+            .withf(|question| question.contains("SEM criptografia"))
+            .times(1)
+            .return_once(|_| Ok(false));
+        mock_user_interface.expect_show_report().never();
+
+        let mut mocks = Mocks::default();
+        mocks.crypto.expect_is_encrypted().returning(|_| true);
+        mocks
+            .todos
+            .expect_report()
+            .return_once(|from, to, grouping| {
+                let mut todo = Todo::new("fatura".to_string());
+                todo.resolved = true;
+                Report::collect(BTreeMap::from([(1, todo)]).iter(), &[], grouping, from, to)
+            });
+
+        let config = Config {
+            file: "test_export_secret.txt".to_string(),
+            ..Config::default()
+        };
+        let mut cli = mocks.cli(mock_user_interface, config);
+        cli.export_report().await.unwrap();
+        let today = Local::now().date_naive().format("%Y-%m-%d");
+        let path = format!("test_export_secret.txt.relatorio-{today}-{today}.md");
+        assert!(!std::path::Path::new(&path).exists());
+    }
+
+    #[tokio::test]
+    async fn test_run_once_for_scripts() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        mock_user_interface
+            .expect_write_styled()
+            .withf(|output, _| output == "2\n")
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mocks
+            .todos
+            .expect_parse_map_write_file()
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+        cli.run_once(Subcommand::Add("Comprar leite".to_string()))
+            .await
+            .unwrap();
+
+        let (mut mock_user_interface, _) = create_mocks();
+        mock_user_interface
+            .expect_write_styled()
+            .withf(|output, _| output == "1\t[ ]\tfoo\n")
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let (_, mocks) = create_mocks();
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+        cli.run_once(Subcommand::List("status:open".to_string()))
+            .await
+            .unwrap();
+
+        let mut mocks = Mocks::default();
+        mocks.crypto.expect_is_encrypted().returning(|_| false);
+        mocks
+            .todos
+            .expect_parse_file_for_todos()
+            .return_once(|_| Ok(LoadReport::default()));
+        mocks
+            .todos
+            .expect_get_one_todo()
+            .withf(|key| key == &9)
+            .return_once(|_| None);
+        let mut cli = mocks.cli(MockUserInterface::new(), Config::default());
+        let error = cli
+            .run_once(Subcommand::Edit(9, "novo".to_string()))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(error.exit_code(), 3);
+        assert!(!cli.autosave.is_dirty());
+        let error = Subcommand::parse(&["pop".to_string()], None).err().unwrap();
+        assert_eq!(error.exit_code(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_notes_have_their_own_command() {
+        let bread = || {
+            let mut bread = Todo::new("pão".to_string());
+            bread.notes = "da padaria".to_string();
+            bread
+        };
+        let (mut mock_user_interface, _) = create_mocks();
+        mock_user_interface
+            .expect_show_table()
+            .return_once(|_, _| Ok(()));
+        mock_user_interface
+            .expect_show_todo()
+            .return_once(|_, _| Ok(()));
+        mock_user_interface
+            .expect_ask_for_notes()
+            .withf(|current| current.is_empty())
+            .return_once(|_| Ok("ver https://exemplo.com // depois".to_string()));
+        let mut mocks = Mocks::default();
+        mocks.todos.expect_is_empty().returning(|| 1);
+        mocks.views.expect_sort_for().returning(|_| Sort::default());
+        mocks
+            .todos
+            .expect_page()
+            .returning(|_, _, number, size| Page {
+                todos: vec![(1, Todo::new("boo".to_string()))],
+                number,
+                size,
+                total: 1,
+            });
+        mocks
+            .todos
+            .expect_get_one_todo()
+            .returning(|_| Some(Todo::new("boo".to_string())));
+        mocks
+            .todos
+            .expect_update()
+            .withf(|key, todo| {
+                key == &1
+                    && todo.message == "boo"
+                    && todo.notes == "ver https://exemplo.com // depois"
+            })
+            .times(1)
+            .return_once(|_, _| true);
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+        cli.edit_notes().await.unwrap();
+        assert!(cli.autosave.is_dirty());
+
+        let mut mocks = Mocks::default();
+        mocks.crypto.expect_is_encrypted().returning(|_| false);
+        mocks
+            .todos
+            .expect_parse_file_for_todos()
+            .returning(|_| Ok(LoadReport::default()));
+        mocks
+            .todos
+            .expect_parse_map_write_file()
+            .returning(|_| Ok(()));
+        mocks
+            .todos
+            .expect_get_one_todo()
+            .withf(|key| key == &1)
+            .returning(move |_| Some(bread()));
+        mocks
+            .todos
+            .expect_update()
+            .withf(|key, todo| {
+                key == &1
+                    && todo.message == "pão integral // sem glúten"
+                    && todo.notes == "da padaria"
+            })
+            .times(1)
+            .return_once(|_, _| true);
+        mocks
+            .todos
+            .expect_update()
+            .withf(|_, todo| todo.message == "pão" && todo.notes.is_empty())
+            .times(1)
+            .return_once(|_, _| true);
+        let mut mock_user_interface = MockUserInterface::new();
+        mock_user_interface
+            .expect_write_styled()
+            .returning(|_, _| Ok(()));
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+        cli.run_once(Subcommand::Edit(
+            1,
+            "pão integral // sem glúten".to_string(),
+        ))
+        .await
+        .unwrap();
+        cli.run_once(Subcommand::Note(1, String::new()))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_shows_ranked_keys() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        mock_user_interface
+            .expect_ask_search()
+            .return_once(|| Ok("~lte".to_string()));
+        mock_user_interface
+            .expect_write_styled()
+            .return_once(|_, _| Ok(()));
+        mock_user_interface
+            .expect_highlight()
+            .times(2)
+            .returning(|_| ());
+        let mut sequence = mockall::Sequence::new();
+        for key in ["4: ", "2: "] {
+            mock_user_interface
+                .expect_show_todo()
+                .withf(move |_, prefix| prefix == key)
+                .times(1)
+                .in_sequence(&mut sequence)
+                .returning(|_, _| Ok(()));
+        }
+
+        mocks.todos.expect_search().return_once(|search| {
+            let mut todos = BTreeMap::new();
+            let mut bread = Todo::new("pão".to_string());
+            bread.notes = "sem leite".to_string();
+            todos.insert(2, bread);
+            todos.insert(4, Todo::new("leite".to_string()));
+            search.rank(todos.iter())
+        });
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+
+        cli.search_todos().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unlock_and_change_passphrase() {
+        let (mut mock_user_interface, _) = create_mocks();
+        let mut mocks = Mocks::default();
+        mocks.crypto.expect_is_encrypted().returning(|_| true);
+        mocks
+            .crypto
+            .expect_unlock()
+            .withf(|_, passphrase| passphrase == "errada")
+            .times(1)
+            .returning(|_, _| Err(StorageError::new(Cause::WrongPassphrase)));
+        mocks
+            .crypto
+            .expect_unlock()
+            .withf(|_, passphrase| passphrase == "segredo")
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mocks
+            .crypto
+            .expect_change_passphrase()
+            .withf(|_, passphrase| passphrase == "nova")
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut answers = vec!["nova", "nova", "segredo", "errada"];
+        mock_user_interface
+            .expect_ask_passphrase()
+            .times(4)
+            .returning(move |_| Ok(answers.pop().unwrap().to_string()));
+        mock_user_interface
+            .expect_show_error()
+            .times(1)
+            .return_const(());
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+
+        cli.unlock_storage().await.unwrap();
+        cli.change_passphrase().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_git_history_reverts_version() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        mock_user_interface
+            .expect_show_commits()
+            .withf(|commits| commits.len() == 2)
+            .return_once(|_| Ok(()));
+        mock_user_interface
+            .expect_ask_key_commit()
+            .return_once(|| Ok(()));
+        mock_user_interface
+            .expect_confirm()
+            .return_once(|_| Ok(true));
+
+        mocks.git.expect_git_log().return_once(|_| {
+            Ok(vec![
+                Commit {
+                    id: "abc123".to_string(),
+                    date: "18/01/2023 10:00".to_string(),
+                    message: "resolve #1: foo".to_string(),
+                },
+                Commit {
+                    id: "def456".to_string(),
+                    date: "17/01/2023 10:00".to_string(),
+                    message: "add #1: foo".to_string(),
+                },
+            ])
+        });
+        mocks
+            .git
+            .expect_revert_to()
+            .withf(|_, commit| commit.id == "abc123")
+            .return_once(|_, _| Ok(1));
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+
+        cli.git_history().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sync_asks_about_conflicts() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        mock_user_interface
+            .expect_ask_sync_file()
+            .return_once(|| Ok("other_todo_list.txt".to_string()));
+        mock_user_interface
+            .expect_ask_conflict()
+            .times(1)
+            .return_once(|_| Ok(Resolution::Both));
+
+        mocks
+            .sync
+            .expect_plan_sync()
+            .withf(|_, other| other == "other_todo_list.txt")
+            .return_once(|_, _| {
+                Ok(Merge {
+                    todos: vec![factori::create!(Todo)],
+                    conflicts: vec![Conflict {
+                        mine: Some(Todo::new("mine".to_string())),
+                        theirs: Some(Todo::new("theirs".to_string())),
+                    }],
+                })
+            });
+        mocks
+            .sync
+            .expect_apply_sync()
+            .withf(|_, _, merge| merge.todos.len() == 3 && merge.conflicts.is_empty())
+            .return_once(|_, _, _| Ok(2));
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+
+        cli.sync_todos().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_renumber_todos() {
+        let (mut mock_user_interface, mut mocks) = create_mocks();
+        mock_user_interface
+            .expect_show_table()
+            .withf(|page, show_keys| page.todos[0].0 == 1 && *show_keys)
+            .return_once(|_, _| Ok(()));
+        mocks.todos.expect_renumber().return_once(|| 2);
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+
+        cli.renumber_todos().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_git_mode_saves_each_change() {
+        let (mut mock_user_interface, _) = create_mocks();
+        mock_user_interface
+            .expect_ask_for_new_todo()
+            .times(2)
+            .returning(|| Ok(factori::create!(Todo)));
+        mock_user_interface
+            .expect_show_todo()
+            .returning(|_, _| Ok(()));
+        let mut mocks = Mocks::default();
+        mocks.todos.expect_insert_todo().times(2).returning(|_| 1);
+        mocks
+            .todos
+            .expect_parse_map_write_file()
+            .times(2)
+            .returning(|_| Ok(()));
+
+        let config = Config {
+            git: true,
+            ..Config::default()
+        };
+        let mut cli = mocks.cli(mock_user_interface, config);
+        tokio::time::pause();
+        for _ in 0..2 {
+            cli.add_todo().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            assert!(!cli.autosave.is_dirty());
+        }
+        cli.autosave.flush().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_external_change_is_reported() {
+        let (mut mock_user_interface, _) = create_mocks();
+        mock_user_interface
+            .expect_show_reload()
+            .withf(|reload| reload.rekeyed == vec![(3, 4)])
+            .times(1)
+            .return_once(|_| Ok(()));
+
+        let mut mocks = Mocks::default();
+        mocks.todos.expect_reload_if_changed().return_once(|_| {
+            Ok(Some(Reload {
+                merged: 1,
+                rekeyed: vec![(3, 4)],
+                ..Reload::default()
+            }))
+        });
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+
+        cli.follow_file().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_undo_and_redo_todos() {
+        let (mut mock_user_interface, mocks) = create_mocks();
+        mock_user_interface
+            .expect_ask_undo_steps()
+            .return_once(|| Ok(()));
+        mock_user_interface
+            .expect_ask_redo_steps()
+            .return_once(|| Ok(()));
+        mock_user_interface
+            .expect_show_error()
+            .times(1)
+            .return_const(());
+
+        let mut cli = mocks.cli(mock_user_interface, Config::default());
+
+        cli.undo_todos().await.unwrap();
+        cli.redo_todos().await.unwrap();
+    }
 }
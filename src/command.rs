@@ -1,3 +1,7 @@
+use crate::smart::SmartList;
+use crate::sort::Move;
+use crate::terminal::TerminalError;
+
 /* #[derive(PartialEq)]
 #[derive(Debug)] */
 pub enum UserCommand {
@@ -6,6 +10,88 @@ pub enum UserCommand {
     Exit,
     Delete,
     ShowTodos,
+    SmartList(SmartList),
     Other,
     Resolve,
+    Undo,
+    Redo,
+    TimeTravel,
+    Compare,
+    Backups,
+    Passphrase,
+    GitHistory,
+    GitSync,
+    Sync,
+    Renumber,
+    Trash,
+    Search,
+    Views,
+    OpenView(String),
+    Reorder,
+    Stats,
+    Report,
+    Notes,
+}
+
+pub enum Subcommand {
+    Add(String),
+    List(String),
+    Done(u32),
+    Edit(u32, String),
+    Note(u32, String),
+    Remove(u32),
+}
+
+impl Subcommand {
+    pub fn parse(words: &[String], filter: Option<String>) -> Result<Option<Self>, TerminalError> {
+        let usage = |message: &str| TerminalError::InvalidArgument(format!("❗ {message} ❗"));
+        let key = |word: Option<&String>| -> Result<u32, TerminalError> {
+            word.ok_or_else(|| usage("Informe o número do TODO"))?
+                .parse()
+                .map_err(TerminalError::ParseErr)
+        };
+        let text = |words: &[String]| match words.join(" ").trim() {
+            "" => Err(usage("Informe o texto do TODO")),
+            text => Ok(text.to_string()),
+        };
+
+        let Some((name, rest)) = words.split_first() else {
+            return match filter {
+                Some(_) => Err(usage("--filter só pode ser usado com list")),
+                None => Ok(None),
+            };
+        };
+        if filter.is_some() && name != "list" {
+            return Err(usage("--filter só pode ser usado com list"));
+        }
+        let subcommand = match name.as_str() {
+            "add" => Self::Add(text(rest)?),
+            "list" => Self::List(filter.unwrap_or_else(|| rest.join(" "))),
+            "done" | "edit" | "note" | "rm" if rest.is_empty() => {
+                return Err(usage("Informe o número do TODO"))
+            }
+            "done" if rest.len() == 1 => Self::Done(key(rest.first())?),
+            "rm" if rest.len() == 1 => Self::Remove(key(rest.first())?),
+            "edit" => Self::Edit(key(rest.first())?, text(&rest[1..])?),
+            "note" => Self::Note(key(rest.first())?, rest[1..].join(" ").trim().to_string()),
+            "done" | "rm" => return Err(usage(&format!("{name} aceita apenas o número do TODO"))),
+            _ => {
+                return Err(usage(&format!(
+                    "Comando desconhecido: {name}. Use add, list, done, edit, note ou rm"
+                )))
+            }
+        };
+        Ok(Some(subcommand))
+    }
+}
+
+pub enum PageTurn {
+    Next,
+    Previous,
+    Stop,
+}
+
+pub enum Arrange {
+    Move(Move),
+    Pin,
 }
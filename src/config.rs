@@ -0,0 +1,347 @@
+use crate::backup::BackupPolicy;
+use crate::command::Subcommand;
+use crate::smart::Calendar;
+use crate::terminal::TerminalError;
+use crate::timeline;
+use chrono::NaiveTime;
+use std::env;
+use std::time::Duration;
+
+const FILE_NAME: &str = "todo_list.txt";
+const AUTOSAVE_MS: u64 = 1500;
+const TRASH_DAYS: u32 = 30;
+
+pub struct Config {
+    pub file: String,
+    pub lenient: bool,
+    pub encrypt: bool,
+    pub git: bool,
+    pub autosave: Duration,
+    pub trash_days: u32,
+    pub backups: BackupPolicy,
+    pub view: Option<String>,
+    pub calendar: Calendar,
+    pub subcommand: Option<Subcommand>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            file: FILE_NAME.to_string(),
+            lenient: false,
+            encrypt: false,
+            git: false,
+            autosave: Duration::from_millis(AUTOSAVE_MS),
+            trash_days: TRASH_DAYS,
+            backups: BackupPolicy::default(),
+            view: None,
+            calendar: Calendar::default(),
+            subcommand: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Self, TerminalError> {
+        Self::resolve(&env::args().skip(1).collect::<Vec<String>>(), |name| {
+            env::var(name).ok().filter(|value| !value.is_empty())
+        })
+    }
+
+    fn resolve(
+        args: &[String],
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, TerminalError> {
+        let mut file = None;
+        let mut view = var("TODO_VIEW");
+        let mut lenient = matches!(var("TODO_LENIENT").as_deref(), Some("1" | "true" | "sim"));
+        let mut encrypt = matches!(var("TODO_ENCRYPT").as_deref(), Some("1" | "true" | "sim"));
+        let mut git = matches!(var("TODO_GIT").as_deref(), Some("1" | "true" | "sim"));
+        let autosave = match var("TODO_AUTOSAVE_MS") {
+            Some(millis) => millis.parse().map_err(|_| {
+                TerminalError::InvalidArgument(format!("❗ TODO_AUTOSAVE_MS inválido: {millis} ❗"))
+            })?,
+            None => AUTOSAVE_MS,
+        };
+        let trash_days = match var("TODO_TRASH_DAYS") {
+            Some(days) => days.parse().map_err(|_| {
+                TerminalError::InvalidArgument(format!("❗ TODO_TRASH_DAYS inválido: {days} ❗"))
+            })?,
+            None => TRASH_DAYS,
+        };
+        let mut backups = BackupPolicy::default();
+        if let Some(keep) = var("TODO_BACKUP_KEEP") {
+            backups.keep_last = keep.parse().map_err(|_| {
+                TerminalError::InvalidArgument(format!("❗ TODO_BACKUP_KEEP inválido: {keep} ❗"))
+            })?;
+        }
+        if let Some(days) = var("TODO_BACKUP_DAYS") {
+            backups.keep_days = days.parse::<u32>().map(i64::from).map_err(|_| {
+                TerminalError::InvalidArgument(format!("❗ TODO_BACKUP_DAYS inválido: {days} ❗"))
+            })?;
+        }
+        if let Some(minutes) = var("TODO_BACKUP_MINUTES") {
+            backups.every = minutes
+                .parse::<u32>()
+                .map(|minutes| chrono::Duration::minutes(minutes.into()))
+                .map_err(|_| {
+                    TerminalError::InvalidArgument(format!(
+                        "❗ TODO_BACKUP_MINUTES inválido: {minutes} ❗"
+                    ))
+                })?;
+        }
+        if backups.keep_last == 0 && backups.keep_days == 0 {
+            return Err(TerminalError::InvalidArgument(
+                "❗ TODO_BACKUP_KEEP e TODO_BACKUP_DAYS não podem ser ambos 0 ❗".to_string(),
+            ));
+        }
+        let mut calendar = Calendar::default();
+        if let Some(start) = var("TODO_DAY_START") {
+            calendar.day_start = NaiveTime::parse_from_str(&start, "%H:%M").map_err(|_| {
+                TerminalError::InvalidArgument(format!(
+                    "❗ TODO_DAY_START inválido: {start} (use HH:MM) ❗"
+                ))
+            })?;
+        }
+        if let Some(weekday) = var("TODO_WEEK_START") {
+            calendar.week_start =
+                timeline::parse_weekday(&weekday.to_lowercase()).ok_or_else(|| {
+                    TerminalError::InvalidArgument(format!(
+                        "❗ TODO_WEEK_START inválido: {weekday} (use segunda, domingo...) ❗"
+                    ))
+                })?;
+        }
+        let mut words = Vec::new();
+        let mut filter = None;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--" => words.extend(args.by_ref().cloned()),
+                "--lenient" => lenient = true,
+                "--encrypt" => encrypt = true,
+                "--git" => git = true,
+                "-f" | "--file" => {
+                    file = Some(args.next().cloned().ok_or_else(|| {
+                        TerminalError::InvalidArgument(format!(
+                            "❗ Informe o caminho após {arg} ❗"
+                        ))
+                    })?)
+                }
+                "--view" => {
+                    view = Some(args.next().cloned().ok_or_else(|| {
+                        TerminalError::InvalidArgument(format!(
+                            "❗ Informe o nome da visão após {arg} ❗"
+                        ))
+                    })?)
+                }
+                "--filter" => {
+                    filter = Some(args.next().cloned().ok_or_else(|| {
+                        TerminalError::InvalidArgument(format!("❗ Informe o filtro após {arg} ❗"))
+                    })?)
+                }
+                _ if !arg.starts_with('-') => words.push(arg.clone()),
+                _ => match (
+                    arg.strip_prefix("--file="),
+                    arg.strip_prefix("--view="),
+                    arg.strip_prefix("--filter="),
+                ) {
+                    (Some(path), _, _) => file = Some(path.to_string()),
+                    (_, Some(name), _) => view = Some(name.to_string()),
+                    (_, _, Some(query)) => filter = Some(query.to_string()),
+                    _ => {
+                        return Err(TerminalError::InvalidArgument(format!(
+                            "❗ Argumento desconhecido: {arg} ❗"
+                        )))
+                    }
+                },
+            }
+        }
+
+        let subcommand = Subcommand::parse(&words, filter)?;
+        let file = file
+            .or_else(|| var("TODO_FILE"))
+            .or_else(|| {
+                var("XDG_DATA_HOME")
+                    .or_else(|| var("HOME").map(|home| format!("{home}/.local/share")))
+                    .map(|data_home| format!("{data_home}/todo/{FILE_NAME}"))
+            })
+            .unwrap_or_else(|| FILE_NAME.to_string());
+
+        Ok(Self {
+            file,
+            lenient,
+            encrypt,
+            git,
+            autosave: Duration::from_millis(autosave),
+            trash_days,
+            backups,
+            view,
+            calendar,
+            subcommand,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn vars(name: &str) -> Option<String> {
+        match name {
+            "TODO_FILE" => Some("/tmp/env_todo.txt".to_string()),
+            "HOME" => Some("/home/foo".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_file_flag_takes_precedence() {
+        let config = Config::resolve(&args(&["--file", "flag.txt"]), vars).unwrap();
+        assert_eq!(config.file, "flag.txt");
+        let config = Config::resolve(&args(&["--file=other.txt"]), vars).unwrap();
+        assert_eq!(config.file, "other.txt");
+        assert!(Config::resolve(&args(&["-f"]), vars).is_err());
+        assert!(Config::resolve(&args(&["--foo"]), vars).is_err());
+        let config = Config::resolve(&args(&["--view", "hoje"]), vars).unwrap();
+        assert_eq!(config.view.as_deref(), Some("hoje"));
+        let config = Config::resolve(&args(&["--view=sprint"]), |name| match name {
+            "TODO_VIEW" => Some("hoje".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(config.view.as_deref(), Some("sprint"));
+    }
+
+    #[test]
+    fn test_subcommands_for_scripts() {
+        let subcommand =
+            |words: &[&str]| Config::resolve(&args(words), vars).map(|config| config.subcommand);
+
+        assert!(subcommand(&[]).unwrap().is_none());
+        assert!(matches!(
+            subcommand(&["add", "Comprar", "leite", "--file", "x.txt"]).unwrap(),
+            Some(Subcommand::Add(message)) if message == "Comprar leite"
+        ));
+        assert!(matches!(
+            subcommand(&["list", "--filter", "status:open #casa"]).unwrap(),
+            Some(Subcommand::List(filter)) if filter == "status:open #casa"
+        ));
+        assert!(matches!(
+            subcommand(&["--filter=tag:casa", "list"]).unwrap(),
+            Some(Subcommand::List(filter)) if filter == "tag:casa"
+        ));
+        assert!(matches!(
+            subcommand(&["done", "3"]).unwrap(),
+            Some(Subcommand::Done(3))
+        ));
+        assert!(matches!(
+            subcommand(&["rm", "4"]).unwrap(),
+            Some(Subcommand::Remove(4))
+        ));
+        assert!(matches!(
+            subcommand(&["note", "3", "ver", "a // b"]).unwrap(),
+            Some(Subcommand::Note(3, notes)) if notes == "ver a // b"
+        ));
+        assert!(matches!(
+            subcommand(&["note", "3"]).unwrap(),
+            Some(Subcommand::Note(3, notes)) if notes.is_empty()
+        ));
+        assert!(matches!(
+            subcommand(&["edit", "3", "--", "-novo", "texto"]).unwrap(),
+            Some(Subcommand::Edit(3, message)) if message == "-novo texto"
+        ));
+        for words in [
+            &["add"][..],
+            &["done"],
+            &["done", "x"],
+            &["rm", "1", "2"],
+            &["edit", "3"],
+            &["pop"],
+            &["add", "leite", "--filter", "x"],
+        ] {
+            assert!(subcommand(words).is_err());
+        }
+    }
+
+    #[test]
+    fn test_env_and_xdg_defaults() {
+        let config = Config::resolve(&[], vars).unwrap();
+        assert_eq!(config.file, "/tmp/env_todo.txt");
+        assert!(!config.lenient);
+        assert_eq!(config.autosave, Duration::from_millis(AUTOSAVE_MS));
+        assert_eq!(config.trash_days, TRASH_DAYS);
+        assert!(config.backups == BackupPolicy::default());
+        assert!(config.view.is_none());
+        assert!(config.calendar == Calendar::default());
+        assert!(
+            Config::resolve(&args(&["--lenient"]), vars)
+                .unwrap()
+                .lenient
+        );
+
+        let config = Config::resolve(&[], |name| match name {
+            "HOME" => Some("/home/foo".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(config.file, "/home/foo/.local/share/todo/todo_list.txt");
+
+        let config = Config::resolve(&[], |name| match name {
+            "XDG_DATA_HOME" => Some("/data".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(config.file, "/data/todo/todo_list.txt");
+
+        let config = Config::resolve(&[], |name| match name {
+            "TODO_AUTOSAVE_MS" => Some("200".to_string()),
+            "TODO_TRASH_DAYS" => Some("7".to_string()),
+            "TODO_BACKUP_KEEP" => Some("3".to_string()),
+            "TODO_BACKUP_DAYS" => Some("14".to_string()),
+            "TODO_BACKUP_MINUTES" => Some("30".to_string()),
+            "TODO_DAY_START" => Some("04:30".to_string()),
+            "TODO_WEEK_START" => Some("Domingo".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(config.autosave, Duration::from_millis(200));
+        assert_eq!(config.trash_days, 7);
+        assert_eq!(config.backups.keep_last, 3);
+        assert_eq!(config.backups.keep_days, 14);
+        assert_eq!(config.backups.every, chrono::Duration::minutes(30));
+        assert_eq!(
+            config.calendar.day_start,
+            NaiveTime::from_hms_opt(4, 30, 0).unwrap()
+        );
+        assert_eq!(config.calendar.week_start, chrono::Weekday::Sun);
+        assert!(Config::resolve(&[], |name| match name {
+            "TODO_WEEK_START" => Some("feriado".to_string()),
+            _ => None,
+        })
+        .is_err());
+        assert!(Config::resolve(&[], |name| match name {
+            "TODO_AUTOSAVE_MS" => Some("logo".to_string()),
+            _ => None,
+        })
+        .is_err());
+        assert!(Config::resolve(&[], |name| match name {
+            "TODO_BACKUP_KEEP" => Some("todos".to_string()),
+            _ => None,
+        })
+        .is_err());
+        assert!(Config::resolve(&[], |name| match name {
+            "TODO_BACKUP_DAYS" => Some("-5".to_string()),
+            _ => None,
+        })
+        .is_err());
+        assert!(Config::resolve(&[], |name| match name {
+            "TODO_BACKUP_KEEP" | "TODO_BACKUP_DAYS" => Some("0".to_string()),
+            _ => None,
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use crate::error::{Cause, StorageError};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use tokio::fs::{read_to_string, write, File};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+const CHECK_TOKEN: &str = "todo";
+const NONCE_LEN: usize = 24;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait CryptoStorage {
+    async fn is_encrypted(&self, path: &str) -> bool;
+    async fn unlock(&mut self, path: &str, passphrase: &str) -> Result<(), StorageError>;
+    async fn change_passphrase(&mut self, path: &str, passphrase: &str)
+        -> Result<(), StorageError>;
+}
+
+#[derive(Clone)]
+pub enum Codec {
+    Plain,
+    Encrypted(XChaCha20Poly1305),
+}
+
+impl Codec {
+    pub fn encode(&self, text: &str) -> Result<String, StorageError> {
+        let Self::Encrypted(cipher) = self else {
+            return Ok(text.to_string());
+        };
+
+        text.lines()
+            .map(|line| {
+                let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+                let sealed = cipher
+                    .encrypt(&nonce, line.as_bytes())
+                    .map_err(|err| StorageError::crypto(format!("erro ao criptografar ({err})")))?;
+                Ok(format!(
+                    "{}\n",
+                    STANDARD.encode([nonce.as_slice(), &sealed].concat())
+                ))
+            })
+            .collect()
+    }
+
+    pub fn decode(&self, text: &str) -> Result<String, StorageError> {
+        let Self::Encrypted(cipher) = self else {
+            return Ok(text.to_string());
+        };
+
+        let mut plain = String::new();
+        for (index, line) in text.lines().enumerate() {
+            if !line.trim().is_empty() {
+                plain.push_str(&Self::open(cipher, line).ok_or_else(|| {
+                    StorageError::crypto("não foi possível descriptografar").line(index + 1)
+                })?);
+            }
+            plain.push('\n');
+        }
+        Ok(plain)
+    }
+
+    pub async fn read(&self, path: &str) -> Result<Option<String>, StorageError> {
+        match read_to_string(path).await {
+            Ok(text) => self.decode(&text).map(Some).map_err(|err| err.at(path)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(StorageError::io(path, err)),
+        }
+    }
+
+    pub async fn read_lines(
+        &self,
+        path: &str,
+        mut each: impl FnMut(usize, &str) -> Result<(), StorageError> + Send,
+    ) -> Result<bool, StorageError> {
+        let file = match File::open(path).await {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(StorageError::io(path, err)),
+        };
+        let mut lines = BufReader::new(file).lines();
+        let mut number = 0;
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(|err| StorageError::io(path, err))?
+        {
+            number += 1;
+            match self {
+                Self::Encrypted(cipher) if !line.trim().is_empty() => {
+                    let plain = Self::open(cipher, &line).ok_or_else(|| {
+                        StorageError::crypto("não foi possível descriptografar")
+                            .line(number)
+                            .at(path)
+                    })?;
+                    each(number, &plain)?
+                }
+                _ => each(number, &line)?,
+            }
+        }
+        Ok(true)
+    }
+
+    pub async fn write(&self, path: &str, text: &str) -> Result<(), StorageError> {
+        let text = self.encode(text).map_err(|err| err.at(path))?;
+        write(path, text.as_bytes())
+            .await
+            .map_err(|err| StorageError::io(path, err))
+    }
+
+    fn open(cipher: &XChaCha20Poly1305, line: &str) -> Option<String> {
+        let sealed = STANDARD.decode(line.trim()).ok()?;
+        if sealed.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, sealed) = sealed.split_at(NONCE_LEN);
+        let plain = cipher.decrypt(XNonce::from_slice(nonce), sealed).ok()?;
+        String::from_utf8(plain).ok()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyHeader {
+    salt: String,
+    check: String,
+}
+
+pub struct Vault;
+
+impl Vault {
+    pub fn path(todo_path: &str) -> String {
+        format!("{todo_path}.key")
+    }
+
+    pub async fn is_encrypted(todo_path: &str) -> bool {
+        tokio::fs::metadata(Self::path(todo_path)).await.is_ok()
+    }
+
+    pub async fn unlock(todo_path: &str, passphrase: &str) -> Result<Codec, StorageError> {
+        let path = Self::path(todo_path);
+        let Some(header) = Codec::Plain.read(&path).await? else {
+            return Ok(Codec::Plain);
+        };
+        let header: KeyHeader = serde_json::from_str(&header).map_err(|err| {
+            StorageError::crypto(format!("cabeçalho da chave inválido ({err})")).at(&path)
+        })?;
+        let salt = STANDARD.decode(&header.salt).map_err(|err| {
+            StorageError::crypto(format!("cabeçalho da chave inválido ({err})")).at(&path)
+        })?;
+
+        let codec = Self::derive(passphrase, &salt)?;
+        match codec.decode(&header.check) {
+            Ok(check) if check.trim_end() == CHECK_TOKEN => Ok(codec),
+            _ => Err(StorageError::new(Cause::WrongPassphrase)),
+        }
+    }
+
+    pub fn seal(passphrase: &str) -> Result<(Codec, String), StorageError> {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let codec = Self::derive(passphrase, &salt)?;
+
+        let header = KeyHeader {
+            salt: STANDARD.encode(salt),
+            check: codec.encode(CHECK_TOKEN)?,
+        };
+        let header = serde_json::to_string(&header).map_err(|err| {
+            StorageError::crypto(format!("erro ao gravar o cabeçalho da chave ({err})"))
+        })?;
+        Ok((codec, header))
+    }
+
+    fn derive(passphrase: &str, salt: &[u8]) -> Result<Codec, StorageError> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|err| StorageError::crypto(format!("erro ao derivar a chave ({err})")))?;
+        Ok(Codec::Encrypted(XChaCha20Poly1305::new(&key.into())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codec_roundtrip() {
+        let codec = Vault::derive("segredo", b"0123456789abcdef").unwrap();
+        let sealed = codec.encode("1-false-foo\n2-true-boo\n").unwrap();
+        assert!(!sealed.contains("foo"));
+        assert_eq!(sealed.lines().count(), 2);
+        assert_eq!(codec.decode(&sealed).unwrap(), "1-false-foo\n2-true-boo\n");
+
+        let other = Vault::derive("outra", b"0123456789abcdef").unwrap();
+        assert!(other.decode(&sealed).is_err());
+        assert_eq!(Codec::Plain.encode("foo\n").unwrap(), "foo\n");
+    }
+
+    #[tokio::test]
+    async fn test_wrong_passphrase_is_rejected() {
+        let path = "test_vault_todo.txt";
+        let (_, header) = Vault::seal("segredo").unwrap();
+        Codec::Plain
+            .write(&Vault::path(path), &header)
+            .await
+            .unwrap();
+        assert!(Vault::is_encrypted(path).await);
+        assert!(Vault::unlock(path, "segredo").await.is_ok());
+        assert!(Vault::unlock(path, "errada")
+            .await
+            .err()
+            .unwrap()
+            .is_wrong_passphrase());
+        tokio::fs::remove_file(Vault::path(path)).await.unwrap();
+    }
+}
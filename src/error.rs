@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result};
+use std::io;
+
+#[derive(Debug)]
+pub enum Cause {
+    Io(io::Error),
+    Parse(String),
+    Crypto(String),
+    WrongPassphrase,
+    Git(String),
+}
+
+impl Display for Cause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::Io(err) if err.kind() == io::ErrorKind::NotFound => {
+                write!(f, "arquivo não encontrado")
+            }
+            Self::Io(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+                write!(f, "permissão negada")
+            }
+            Self::Io(err) => write!(f, "erro de leitura/escrita ({err})"),
+            Self::Parse(reason) | Self::Crypto(reason) | Self::Git(reason) => {
+                write!(f, "{reason}")
+            }
+            Self::WrongPassphrase => write!(f, "senha incorreta"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct StorageError {
+    pub path: Option<String>,
+    pub line: Option<usize>,
+    pub cause: Cause,
+}
+
+impl StorageError {
+    pub fn new(cause: Cause) -> Self {
+        Self {
+            path: None,
+            line: None,
+            cause,
+        }
+    }
+
+    pub fn io(path: &str, err: io::Error) -> Self {
+        Self::new(Cause::Io(err)).at(path)
+    }
+
+    pub fn parse(reason: impl Into<String>) -> Self {
+        Self::new(Cause::Parse(reason.into()))
+    }
+
+    pub fn crypto(reason: impl Into<String>) -> Self {
+        Self::new(Cause::Crypto(reason.into()))
+    }
+
+    pub fn git(reason: impl Into<String>) -> Self {
+        Self::new(Cause::Git(reason.into()))
+    }
+
+    pub fn at(mut self, path: &str) -> Self {
+        self.path.get_or_insert_with(|| path.to_string());
+        self
+    }
+
+    pub fn line(mut self, line: usize) -> Self {
+        self.line.get_or_insert(line);
+        self
+    }
+
+    pub fn is_wrong_passphrase(&self) -> bool {
+        matches!(self.cause, Cause::WrongPassphrase)
+    }
+}
+
+impl Display for StorageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match (&self.path, self.line) {
+            (Some(path), Some(line)) => write!(f, "linha {line} de {path}: {}", self.cause),
+            (Some(path), None) => write!(f, "{path}: {}", self.cause),
+            (None, Some(line)) => write!(f, "linha {line}: {}", self.cause),
+            (None, None) => write!(f, "{}", self.cause),
+        }
+    }
+}
+
+impl Error for StorageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.cause {
+            Cause::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_includes_path_and_line() {
+        let error = StorageError::parse("status inválido 'tru'")
+            .line(12)
+            .at("~/todo.txt");
+        assert_eq!(
+            error.to_string(),
+            "linha 12 de ~/todo.txt: status inválido 'tru'"
+        );
+
+        let error = StorageError::io(
+            "todo.txt",
+            io::Error::new(io::ErrorKind::NotFound, "missing"),
+        );
+        assert_eq!(error.to_string(), "todo.txt: arquivo não encontrado");
+        assert!(error.source().is_some());
+    }
+}
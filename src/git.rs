@@ -0,0 +1,272 @@
+use crate::error::StorageError;
+use std::path::Path;
+use tokio::fs::canonicalize;
+use tokio::process::Command;
+
+pub struct Commit {
+    pub id: String,
+    pub date: String,
+    pub message: String,
+}
+
+pub struct GitRepo {
+    dir: String,
+}
+
+pub enum Upstream {
+    Missing,
+    UpToDate,
+    Behind,
+    Diverged,
+}
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait GitStorage {
+    async fn git_log(&self, path: &str) -> Result<Vec<Commit>, StorageError>;
+    async fn revert_to(&mut self, path: &str, commit: &Commit) -> Result<u32, StorageError>;
+    async fn git_sync(&mut self, path: &str) -> Result<(), StorageError>;
+}
+
+#[derive(Default)]
+pub struct Versioning {
+    enabled: bool,
+    changes: Vec<String>,
+}
+
+impl Versioning {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn record(&mut self, change: String) {
+        self.changes.push(change);
+    }
+
+    pub fn replace(&mut self, change: String) {
+        self.changes = vec![change];
+    }
+
+    pub fn clear(&mut self) {
+        self.changes.clear();
+    }
+
+    pub fn repo(&self, todo_path: &str) -> Result<GitRepo, StorageError> {
+        if !self.enabled {
+            return Err(StorageError::git("o modo git está desativado (use --git)"));
+        }
+        Ok(GitRepo::new(todo_path))
+    }
+
+    pub async fn commit(
+        &mut self,
+        todo_path: &str,
+        files: &[String],
+        fallback: &str,
+    ) -> Result<(), StorageError> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let message = match self.changes.as_slice() {
+            [] => fallback.to_string(),
+            [change] => change.clone(),
+            changes => format!("{} changes\n\n{}", changes.len(), changes.join("\n")),
+        };
+        GitRepo::new(todo_path).commit(files, &message).await?;
+        self.changes.clear();
+        Ok(())
+    }
+}
+
+impl GitRepo {
+    pub fn new(todo_path: &str) -> Self {
+        let dir = Path::new(todo_path)
+            .parent()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .filter(|dir| !dir.is_empty())
+            .unwrap_or_else(|| ".".to_string());
+        Self { dir }
+    }
+
+    pub fn file_name(todo_path: &str) -> String {
+        Path::new(todo_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| todo_path.to_string())
+    }
+
+    async fn git(&self, args: &[&str]) -> Result<String, StorageError> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.dir)
+            .args(args)
+            .output()
+            .await
+            .map_err(|err| StorageError::io(&self.dir, err))?;
+
+        if !output.status.success() {
+            return Err(StorageError::git(format!(
+                "git {} falhou: {}",
+                args.first().unwrap_or(&""),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+            .at(&self.dir));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    pub async fn init(&self) -> Result<(), StorageError> {
+        let dir = canonicalize(&self.dir)
+            .await
+            .map_err(|err| StorageError::io(&self.dir, err))?;
+        let toplevel = self.git(&["rev-parse", "--show-toplevel"]).await;
+        if !matches!(toplevel, Ok(toplevel) if Path::new(toplevel.trim()) == dir) {
+            self.git(&["init", "--quiet"]).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn commit(&self, files: &[String], message: &str) -> Result<bool, StorageError> {
+        let existing = files
+            .iter()
+            .filter(|file| Path::new(&self.dir).join(file).exists())
+            .map(|file| file.as_str())
+            .collect::<Vec<&str>>();
+        self.git(&[&["add", "--"], existing.as_slice()].concat())
+            .await?;
+        if self
+            .git(&[&["diff", "--cached", "--quiet", "--"], existing.as_slice()].concat())
+            .await
+            .is_ok()
+        {
+            return Ok(false);
+        }
+
+        let identity = self.identity().await;
+        self.git(
+            &[
+                identity.as_slice(),
+                &["commit", "--quiet", "-m", message, "--"],
+                existing.as_slice(),
+            ]
+            .concat(),
+        )
+        .await?;
+        Ok(true)
+    }
+
+    async fn identity(&self) -> Vec<&'static str> {
+        match self.git(&["config", "user.email"]).await {
+            Ok(_) => Vec::new(),
+            Err(_) => vec!["-c", "user.name=todo", "-c", "user.email=todo@localhost"],
+        }
+    }
+
+    pub async fn log(&self, files: &[String], limit: usize) -> Result<Vec<Commit>, StorageError> {
+        let limit = format!("-n{limit}");
+        let files = files
+            .iter()
+            .map(|file| file.as_str())
+            .collect::<Vec<&str>>();
+        let log = self
+            .git(
+                &[
+                    &[
+                        "log",
+                        &limit,
+                        "--date=format:%d/%m/%Y %H:%M",
+                        "--format=%h%x09%ad%x09%s",
+                        "--",
+                    ],
+                    files.as_slice(),
+                ]
+                .concat(),
+            )
+            .await?;
+
+        Ok(log
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                Some(Commit {
+                    id: fields.next()?.to_string(),
+                    date: fields.next()?.to_string(),
+                    message: fields.next()?.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    pub async fn show(&self, revision: &str, file: &str) -> Result<Option<String>, StorageError> {
+        let object = format!("{revision}:./{file}");
+        if self.git(&["cat-file", "-e", &object]).await.is_err() {
+            return Ok(None);
+        }
+        Ok(Some(self.git(&["show", &object]).await?))
+    }
+
+    pub async fn fetch(&self) -> Result<Upstream, StorageError> {
+        if self
+            .git(&["rev-parse", "--abbrev-ref", "@{u}"])
+            .await
+            .is_err()
+        {
+            return Ok(Upstream::Missing);
+        }
+        self.git(&["fetch", "--quiet"]).await?;
+        let counts = self
+            .git(&["rev-list", "--left-right", "--count", "HEAD...@{u}"])
+            .await?;
+        let counts = counts
+            .split_whitespace()
+            .map(|count| count.parse::<u32>().unwrap_or_default())
+            .collect::<Vec<u32>>();
+        Ok(match counts.as_slice() {
+            [_, 0] => Upstream::UpToDate,
+            [0, _] => Upstream::Behind,
+            _ => Upstream::Diverged,
+        })
+    }
+
+    pub async fn merge_base(&self) -> Result<String, StorageError> {
+        Ok(self
+            .git(&["merge-base", "HEAD", "@{u}"])
+            .await?
+            .trim()
+            .to_string())
+    }
+
+    pub async fn fast_forward(&self) -> Result<(), StorageError> {
+        self.git(&["merge", "--quiet", "--ff-only", "@{u}"]).await?;
+        Ok(())
+    }
+
+    pub async fn merge_ours(&self, message: &str) -> Result<(), StorageError> {
+        let identity = self.identity().await;
+        self.git(
+            &[
+                identity.as_slice(),
+                &[
+                    "merge", "--quiet", "--no-ff", "-s", "ours", "-m", message, "@{u}",
+                ],
+            ]
+            .concat(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn push(&self) -> Result<(), StorageError> {
+        self.git(&["push", "--quiet", "--set-upstream", "origin", "HEAD"])
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use crate::todo::Todo;
+use serde::{Deserialize, Serialize};
+
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Operation {
+    Insert { key: u32, todo: Todo },
+    Update { key: u32, before: Todo, after: Todo },
+    Resolve { key: u32, before: Todo },
+    Remove { key: u32, todo: Todo },
+    Restore { key: u32, todo: Todo },
+    Group { operations: Vec<Operation> },
+}
+
+impl Operation {
+    fn rekey(&mut self, id: &str, key: u32) {
+        match self {
+            Operation::Insert { key: old, todo }
+            | Operation::Update {
+                key: old,
+                before: todo,
+                ..
+            }
+            | Operation::Resolve {
+                key: old,
+                before: todo,
+            }
+            | Operation::Remove { key: old, todo }
+            | Operation::Restore { key: old, todo } => {
+                if todo.id == id {
+                    *old = key;
+                }
+            }
+            Operation::Group { operations } => {
+                for operation in operations {
+                    operation.rekey(id, key);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct History {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+}
+
+impl History {
+    pub fn path(todo_path: &str) -> String {
+        format!("{todo_path}.history")
+    }
+
+    pub fn record(&mut self, operation: Operation) {
+        self.redo.clear();
+        self.undo.push(operation);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn rekey(&mut self, id: &str, key: u32) {
+        for operation in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            operation.rekey(id, key);
+        }
+    }
+
+    pub fn undo(&mut self) -> Option<Operation> {
+        let operation = self.undo.pop()?;
+        self.redo.push(operation.clone());
+        Some(operation)
+    }
+
+    pub fn redo(&mut self) -> Option<Operation> {
+        let operation = self.redo.pop()?;
+        self.undo.push(operation.clone());
+        Some(operation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_clears_redo() {
+        let mut history = History::default();
+        history.record(Operation::Remove {
+            key: 1,
+            todo: Todo::new("foo".to_string()),
+        });
+        assert!(history.undo().is_some());
+        assert!(history.undo().is_none());
+
+        history.record(Operation::Insert {
+            key: 2,
+            todo: Todo::new("boo".to_string()),
+        });
+        assert!(history.redo().is_none());
+    }
+}
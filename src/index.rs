@@ -0,0 +1,205 @@
+use crate::todo::Todo;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+pub enum Lookup {
+    All,
+    Open,
+    Resolved,
+    Tag(String),
+    DueBy(NaiveDate),
+}
+
+pub struct Page {
+    pub todos: Vec<(u32, Todo)>,
+    pub number: usize,
+    pub size: usize,
+    pub total: usize,
+}
+
+impl Page {
+    pub fn first(&self) -> usize {
+        self.number * self.size + 1
+    }
+
+    pub fn last(&self) -> usize {
+        self.number * self.size + self.todos.len()
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.last() < self.total
+    }
+}
+
+#[derive(Default)]
+pub struct Index {
+    ids: HashMap<String, u32>,
+    open: BTreeSet<u32>,
+    resolved: BTreeSet<u32>,
+    tags: HashMap<String, BTreeSet<u32>>,
+    due: BTreeMap<NaiveDate, BTreeSet<u32>>,
+    ordered: BTreeSet<(bool, i64, u32)>,
+}
+
+impl Index {
+    pub fn build(todos: &BTreeMap<u32, Todo>) -> Self {
+        let mut index = Self::default();
+        for (key, todo) in todos {
+            index.insert(*key, todo);
+        }
+        index
+    }
+
+    pub fn insert(&mut self, key: u32, todo: &Todo) {
+        self.ids.insert(todo.id.clone(), key);
+        self.ordered.insert((!todo.pinned, todo.place(key), key));
+        match todo.resolved {
+            true => self.resolved.insert(key),
+            false => self.open.insert(key),
+        };
+        for tag in todo.tags() {
+            self.tags.entry(tag).or_default().insert(key);
+        }
+        if let Some(due) = todo.due() {
+            self.due.entry(due).or_default().insert(key);
+        }
+    }
+
+    pub fn remove(&mut self, key: u32, todo: &Todo) {
+        if self.ids.get(&todo.id) == Some(&key) {
+            self.ids.remove(&todo.id);
+        }
+        self.ordered.remove(&(!todo.pinned, todo.place(key), key));
+        self.open.remove(&key);
+        self.resolved.remove(&key);
+        for tag in todo.tags() {
+            if let Some(keys) = self.tags.get_mut(&tag) {
+                keys.remove(&key);
+                if keys.is_empty() {
+                    self.tags.remove(&tag);
+                }
+            }
+        }
+        if let Some(due) = todo.due() {
+            if let Some(keys) = self.due.get_mut(&due) {
+                keys.remove(&key);
+                if keys.is_empty() {
+                    self.due.remove(&due);
+                }
+            }
+        }
+    }
+
+    pub fn key_of(&self, id: &str) -> Option<u32> {
+        self.ids.get(id).copied()
+    }
+
+    pub fn count(&self, lookup: &Lookup) -> usize {
+        match lookup {
+            Lookup::All => self.open.len() + self.resolved.len(),
+            Lookup::Open => self.open.len(),
+            Lookup::Resolved => self.resolved.len(),
+            Lookup::Tag(tag) => self.tags.get(tag).map_or(0, BTreeSet::len),
+            Lookup::DueBy(date) => self.due.range(..=date).map(|(_, keys)| keys.len()).sum(),
+        }
+    }
+
+    pub fn contains(&self, lookup: &Lookup, key: u32) -> bool {
+        match lookup {
+            Lookup::All => self.open.contains(&key) || self.resolved.contains(&key),
+            Lookup::Open => self.open.contains(&key),
+            Lookup::Resolved => self.resolved.contains(&key),
+            Lookup::Tag(tag) => self.tags.get(tag).is_some_and(|keys| keys.contains(&key)),
+            Lookup::DueBy(date) => self.due.range(..=date).any(|(_, keys)| keys.contains(&key)),
+        }
+    }
+
+    pub fn ordered<'a>(&'a self, lookup: &'a Lookup) -> Box<dyn Iterator<Item = u32> + 'a> {
+        let keys = self.ordered.iter().map(|(_, _, key)| *key);
+        match lookup {
+            Lookup::All => Box::new(keys),
+            lookup => Box::new(keys.filter(move |key| self.contains(lookup, *key))),
+        }
+    }
+
+    pub fn places(&self, pinned: bool) -> Vec<(i64, u32)> {
+        self.ordered
+            .range((!pinned, i64::MIN, 0)..=(!pinned, i64::MAX, u32::MAX))
+            .map(|(_, place, key)| (*place, *key))
+            .collect()
+    }
+
+    pub fn last_place(&self, pinned: bool) -> Option<i64> {
+        self.ordered
+            .range((!pinned, i64::MIN, 0)..=(!pinned, i64::MAX, u32::MAX))
+            .next_back()
+            .map(|(_, place, _)| *place)
+    }
+
+    pub fn keys<'a>(&'a self, lookup: &Lookup) -> Box<dyn Iterator<Item = u32> + 'a> {
+        match lookup {
+            Lookup::All => Box::new(self.open.union(&self.resolved).copied()),
+            Lookup::Open => Box::new(self.open.iter().copied()),
+            Lookup::Resolved => Box::new(self.resolved.iter().copied()),
+            Lookup::Tag(tag) => Box::new(self.tags.get(tag).into_iter().flatten().copied()),
+            Lookup::DueBy(date) => Box::new(
+                self.due
+                    .range(..=*date)
+                    .flat_map(|(_, keys)| keys.iter().copied()),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn todo(message: &str, resolved: bool) -> Todo {
+        let mut todo = Todo::new(message.to_string());
+        todo.resolved = resolved;
+        todo
+    }
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_index_tracks_status_tags_and_due_dates() {
+        let mut todos = BTreeMap::new();
+        todos.insert(1, todo("leite #mercado due:2023-03-01", false));
+        todos.insert(2, todo("pão #Mercado #padaria", true));
+        todos.insert(3, todo("relatório due:2023-02-10", false));
+        let mut index = Index::build(&todos);
+
+        let keys = |index: &Index, lookup: Lookup| index.keys(&lookup).collect::<Vec<u32>>();
+        assert_eq!(keys(&index, Lookup::All), vec![1, 2, 3]);
+        assert_eq!(keys(&index, Lookup::Open), vec![1, 3]);
+        assert_eq!(keys(&index, Lookup::Tag("mercado".to_string())), vec![1, 2]);
+        assert_eq!(keys(&index, Lookup::DueBy(day(2023, 3, 1))), vec![3, 1]);
+        assert_eq!(index.count(&Lookup::DueBy(day(2023, 2, 28))), 1);
+        assert_eq!(index.key_of(&todos[&2].id), Some(2));
+
+        index.remove(2, &todos[&2]);
+        assert_eq!(index.count(&Lookup::Tag("mercado".to_string())), 1);
+        assert!(index
+            .keys(&Lookup::Tag("padaria".to_string()))
+            .next()
+            .is_none());
+        assert_eq!(index.key_of(&todos[&2].id), None);
+
+        let mut pinned = todo("fatura", false);
+        pinned.pinned = true;
+        pinned.position = Some(9_999_999);
+        index.insert(4, &pinned);
+        assert_eq!(
+            index.ordered(&Lookup::All).collect::<Vec<u32>>(),
+            vec![4, 1, 3]
+        );
+        assert_eq!(index.ordered(&Lookup::Open).count(), 3);
+        assert_eq!(index.places(true), vec![(9_999_999, 4)]);
+        assert_eq!(index.last_place(true), Some(9_999_999));
+        assert_eq!(index.last_place(false), Some(todos[&3].place(3)));
+    }
+}
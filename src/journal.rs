@@ -0,0 +1,247 @@
+use crate::crypto::Codec;
+use crate::error::StorageError;
+use crate::recovery::RejectedLine;
+use crate::todo::{Stamps, Todo};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::fs::{write, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "event")]
+pub enum Event {
+    TodoCreated { key: u32, todo: Todo },
+    MessageChanged { key: u32, message: String },
+    NotesChanged { key: u32, notes: String },
+    Resolved { key: u32 },
+    Reopened { key: u32 },
+    Removed { key: u32 },
+    Renumbered { moves: Vec<(u32, u32)> },
+    Trashed { key: u32 },
+    Purged { ids: Vec<String> },
+    Moved { key: u32, position: i64 },
+    Pinned { key: u32, pinned: bool },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+impl Event {
+    pub fn with_key(self, key: u32) -> Self {
+        match self {
+            Event::TodoCreated { todo, .. } => Event::TodoCreated { key, todo },
+            Event::MessageChanged { message, .. } => Event::MessageChanged { key, message },
+            Event::NotesChanged { notes, .. } => Event::NotesChanged { key, notes },
+            Event::Resolved { .. } => Event::Resolved { key },
+            Event::Reopened { .. } => Event::Reopened { key },
+            Event::Removed { .. } => Event::Removed { key },
+            Event::Renumbered { moves } => Event::Renumbered { moves },
+            Event::Trashed { .. } => Event::Trashed { key },
+            Event::Purged { ids } => Event::Purged { ids },
+            Event::Moved { position, .. } => Event::Moved { key, position },
+            Event::Pinned { pinned, .. } => Event::Pinned { key, pinned },
+        }
+    }
+
+    pub fn clashes(&self, base: &Stamps, theirs: &Todo) -> bool {
+        let changed = &theirs.stamps;
+        if *base == Stamps::default() {
+            return false;
+        }
+        match self {
+            Event::MessageChanged { message, .. } => {
+                changed.message != base.message && *message != theirs.message
+            }
+            Event::NotesChanged { notes, .. } => {
+                changed.notes != base.notes && *notes != theirs.notes
+            }
+            Event::Resolved { .. } | Event::Reopened { .. } => {
+                changed.resolved != base.resolved
+                    && theirs.resolved != matches!(self, Event::Resolved { .. })
+            }
+            Event::Moved { position, .. } => {
+                changed.position != base.position && Some(*position) != theirs.position
+            }
+            Event::Pinned { pinned, .. } => {
+                changed.pinned != base.pinned && *pinned != theirs.pinned
+            }
+            Event::Removed { .. } | Event::Trashed { .. } => changed != base,
+            _ => false,
+        }
+    }
+}
+
+impl Entry {
+    pub fn now(event: Event) -> Self {
+        Self {
+            at: Utc::now(),
+            event,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Outbox {
+    pub pending: Vec<Entry>,
+    pub targets: Vec<(String, Stamps)>,
+    pub logged: usize,
+}
+
+impl Outbox {
+    pub fn push(&mut self, entry: Entry, target: (String, Stamps)) {
+        self.pending.push(entry);
+        self.targets.push(target);
+    }
+
+    pub fn sent(&mut self) {
+        self.logged += self.pending.len();
+        self.pending.clear();
+        self.targets.clear();
+    }
+}
+
+pub struct Journal;
+
+impl Journal {
+    pub fn log_path(todo_path: &str) -> String {
+        format!("{todo_path}.log")
+    }
+
+    pub fn archive_path(todo_path: &str) -> String {
+        format!("{todo_path}.archive")
+    }
+
+    pub async fn read(path: &str, codec: &Codec) -> Result<Vec<Entry>, StorageError> {
+        let (entries, rejected) = Self::read_lenient(path, codec).await?;
+        match rejected.first() {
+            Some(rejected) => Err(StorageError::parse(format!(
+                "evento inválido ({})",
+                rejected.reason
+            ))
+            .line(rejected.line)
+            .at(path)),
+            None => Ok(entries),
+        }
+    }
+
+    pub async fn read_lenient(
+        path: &str,
+        codec: &Codec,
+    ) -> Result<(Vec<Entry>, Vec<RejectedLine>), StorageError> {
+        let log = codec.read(path).await?.unwrap_or_default();
+        Ok(Self::parse(path, &log))
+    }
+
+    pub fn parse(path: &str, log: &str) -> (Vec<Entry>, Vec<RejectedLine>) {
+        let mut entries = Vec::new();
+        let mut rejected = Vec::new();
+        for (index, line) in log.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(entry) => entries.push(entry),
+                Err(err) => rejected.push(RejectedLine {
+                    file: path.to_string(),
+                    line: index + 1,
+                    content: line.to_string(),
+                    reason: err.to_string(),
+                }),
+            }
+        }
+        (entries, rejected)
+    }
+
+    pub async fn append(path: &str, entries: &[Entry], codec: &Codec) -> Result<(), StorageError> {
+        let mut lines = String::new();
+        for entry in entries {
+            let line = serde_json::to_string(entry).map_err(|err| {
+                StorageError::parse(format!("erro ao gravar o evento ({err})")).at(path)
+            })?;
+            lines.push_str(&line);
+            lines.push('\n');
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(|err| StorageError::io(path, err))?;
+        let lines = codec.encode(&lines).map_err(|err| err.at(path))?;
+        file.write_all(lines.as_bytes())
+            .await
+            .map_err(|err| StorageError::io(path, err))
+    }
+
+    pub async fn compact(
+        todo_path: &str,
+        snapshot: &str,
+        codec: &Codec,
+    ) -> Result<(), StorageError> {
+        let log_path = Self::log_path(todo_path);
+        let (entries, _) = Self::read_lenient(&log_path, codec).await?;
+        Self::append(&Self::archive_path(todo_path), &entries, codec).await?;
+        codec.write(todo_path, snapshot).await?;
+        write(&log_path, b"")
+            .await
+            .map_err(|err| StorageError::io(&log_path, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_append_and_compact_journal() {
+        let path = "test_journal_todo.txt";
+        Journal::append(
+            &Journal::log_path(path),
+            &[
+                Entry::now(Event::TodoCreated {
+                    key: 1,
+                    todo: Todo::new("foo\n".to_string()),
+                }),
+                Entry::now(Event::Resolved { key: 1 }),
+            ],
+            &Codec::Plain,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            Journal::read(&Journal::log_path(path), &Codec::Plain)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        Journal::compact(path, "1-true-foo\n", &Codec::Plain)
+            .await
+            .unwrap();
+        assert!(Journal::read(&Journal::log_path(path), &Codec::Plain)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            Journal::read(&Journal::archive_path(path), &Codec::Plain)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        for file in [
+            path.to_string(),
+            Journal::log_path(path),
+            Journal::archive_path(path),
+        ] {
+            tokio::fs::remove_file(file).await.unwrap();
+        }
+    }
+}
@@ -0,0 +1,28 @@
+pub mod autosave;
+pub mod backup;
+pub mod cli;
+pub mod command;
+pub mod config;
+pub mod crypto;
+pub mod error;
+pub mod git;
+pub mod history;
+pub mod index;
+pub mod journal;
+pub mod query;
+pub mod recovery;
+pub mod report;
+pub mod search;
+pub mod smart;
+pub mod sort;
+pub mod stats;
+pub mod summary;
+pub mod sync;
+pub mod table;
+pub mod terminal;
+pub mod timeline;
+pub mod todo;
+pub mod todos;
+pub mod trash;
+pub mod views;
+pub mod watch;
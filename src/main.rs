@@ -1,17 +1,38 @@
-mod cli;
-mod command;
-mod terminal;
-mod todo;
-mod todos;
-use crate::cli::TodoCli;
-use crate::terminal::Terminal;
-use crate::todos::Todos;
+use std::process;
+use todo::cli::TodoCli;
+use todo::config::Config;
+use todo::terminal::{Terminal, UserInterface};
+use todo::todos::Todos;
 
 #[tokio::main]
 async fn main() {
-    let mut cli = TodoCli::new(Terminal::new(), Todos::new());
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(error) => {
+            let code = error.exit_code();
+            Terminal::new().show_error(error);
+            process::exit(code);
+        }
+    };
+    let subcommand = config.subcommand.take();
+    let mut cli = TodoCli::new(
+        Terminal::new(),
+        Todos::new()
+            .with_backups(config.backups)
+            .with_lenient(config.lenient)
+            .with_git(config.git)
+            .with_trash_retention(config.trash_days)
+            .with_calendar(config.calendar),
+        config,
+    );
 
-    if let Err(error) = cli.run().await {
-        cli.user_interface.show_error(error)
+    let result = match subcommand {
+        Some(subcommand) => cli.run_once(subcommand).await,
+        None => cli.run().await,
+    };
+    if let Err(error) = result {
+        let code = error.exit_code();
+        cli.user_interface.show_error(error);
+        process::exit(code);
     }
 }
@@ -0,0 +1,402 @@
+use crate::index::Lookup;
+use crate::smart::{Calendar, SmartList};
+use crate::terminal::TerminalError;
+use crate::todo::{Priority, Todo};
+use chrono::{Duration, Local, NaiveDate};
+use std::ops::Range;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Compare {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Compare {
+    fn split(operator: &str) -> (Self, &str) {
+        for (prefix, compare) in [
+            ("<=", Self::Le),
+            (">=", Self::Ge),
+            ("<", Self::Lt),
+            (">", Self::Gt),
+            ("=", Self::Eq),
+            (":", Self::Eq),
+        ] {
+            if let Some(value) = operator.strip_prefix(prefix) {
+                return (compare, value);
+            }
+        }
+        (Self::Eq, operator)
+    }
+
+    fn holds<T: Ord>(self, left: T, right: T) -> bool {
+        match self {
+            Self::Eq => left == right,
+            Self::Lt => left < right,
+            Self::Le => left <= right,
+            Self::Gt => left > right,
+            Self::Ge => left >= right,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum Day {
+    On(NaiveDate),
+    In(i64),
+}
+
+impl Day {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "hoje" | "today" => Some(Self::In(0)),
+            "amanhã" | "amanha" | "tomorrow" => Some(Self::In(1)),
+            value => {
+                if let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+                    return Some(Self::On(day));
+                }
+                let amount = |unit| value.strip_suffix(unit)?.parse::<i64>().ok();
+                amount('d')
+                    .map(Self::In)
+                    .or_else(|| amount('w').map(|weeks| Self::In(weeks.saturating_mul(7))))
+            }
+        }
+    }
+
+    fn checked(&self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::On(day) => Some(*day),
+            Self::In(days) => today.checked_add_signed(Duration::try_days(*days)?),
+        }
+    }
+
+    fn resolve(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            Self::In(days) if *days < 0 => self.checked(today).unwrap_or(NaiveDate::MIN),
+            _ => self.checked(today).unwrap_or(NaiveDate::MAX),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum Condition {
+    Status(bool),
+    Tag(String),
+    Due(Compare, Day),
+    Priority(Compare, Priority),
+    Text(String),
+    Smart(SmartList, Calendar),
+}
+
+impl Condition {
+    fn matches(&self, todo: &Todo, today: NaiveDate) -> bool {
+        match self {
+            Self::Status(resolved) => todo.resolved == *resolved,
+            Self::Tag(tag) => todo.tags().contains(tag),
+            Self::Due(compare, day) => todo
+                .due()
+                .is_some_and(|due| compare.holds(due, day.resolve(today))),
+            Self::Priority(compare, priority) => todo
+                .priority()
+                .is_some_and(|own| compare.holds(own, *priority)),
+            Self::Text(text) => {
+                todo.message.to_lowercase().contains(text)
+                    || todo.notes.to_lowercase().contains(text)
+            }
+            Self::Smart(list, calendar) => list.matches(todo, today, calendar),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct Term {
+    negated: bool,
+    condition: Condition,
+}
+
+impl Term {
+    fn lookup(&self, today: NaiveDate) -> Option<Lookup> {
+        if self.negated {
+            return None;
+        }
+        match &self.condition {
+            Condition::Status(false) => Some(Lookup::Open),
+            Condition::Status(true) => Some(Lookup::Resolved),
+            Condition::Tag(tag) => Some(Lookup::Tag(tag.clone())),
+            Condition::Due(Compare::Le, day) => Some(Lookup::DueBy(day.resolve(today))),
+            Condition::Due(Compare::Lt, day) => day.resolve(today).pred_opt().map(Lookup::DueBy),
+            _ => None,
+        }
+    }
+}
+
+struct Token<'a> {
+    span: Range<usize>,
+    negated: bool,
+    quoted: bool,
+    text: &'a str,
+}
+
+#[derive(Clone, Default, PartialEq)]
+pub struct Query {
+    source: String,
+    terms: Vec<Term>,
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Self, TerminalError> {
+        let terms = tokenize(query)?
+            .iter()
+            .map(|token| parse_term(query, token))
+            .collect::<Result<Vec<Term>, TerminalError>>()?;
+        Ok(Self {
+            source: query.trim().to_string(),
+            terms,
+        })
+    }
+
+    pub fn smart(list: SmartList, calendar: Calendar) -> Self {
+        Self {
+            source: format!("lista:{}", list.name()),
+            terms: vec![Term {
+                negated: false,
+                condition: Condition::Smart(list, calendar),
+            }],
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, todo: &Todo, today: NaiveDate) -> bool {
+        self.terms
+            .iter()
+            .all(|term| term.condition.matches(todo, today) != term.negated)
+    }
+
+    pub fn plan(&self, today: NaiveDate) -> (Lookup, Query) {
+        for (position, term) in self.terms.iter().enumerate() {
+            if let Some(lookup) = term.lookup(today) {
+                let mut rest = self.terms.clone();
+                rest.remove(position);
+                return (
+                    lookup,
+                    Self {
+                        source: self.source.clone(),
+                        terms: rest,
+                    },
+                );
+            }
+        }
+        (Lookup::All, self.clone())
+    }
+}
+
+fn invalid(query: &str, span: Range<usize>, reason: &str) -> TerminalError {
+    TerminalError::InvalidQuery {
+        query: query.to_string(),
+        span,
+        reason: reason.to_string(),
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token<'_>>, TerminalError> {
+    let mut tokens = Vec::new();
+    let mut rest = query.trim_start();
+    while !rest.is_empty() {
+        let start = query.len() - rest.len();
+        let negated = rest.starts_with('-');
+        let body = &rest[usize::from(negated)..];
+        let (text, quoted, length) = match body.strip_prefix('"') {
+            Some(phrase) => {
+                let end = phrase
+                    .find('"')
+                    .ok_or_else(|| invalid(query, start..query.len(), "aspas sem fechamento"))?;
+                (&phrase[..end], true, end + 2)
+            }
+            None => {
+                let end = body.find(char::is_whitespace).unwrap_or(body.len());
+                (&body[..end], false, end)
+            }
+        };
+        let end = start + usize::from(negated) + length;
+        if text.trim().is_empty() {
+            return Err(invalid(query, start..end, "condição vazia"));
+        }
+        tokens.push(Token {
+            span: start..end,
+            negated,
+            quoted,
+            text,
+        });
+        rest = query[end..].trim_start();
+    }
+    Ok(tokens)
+}
+
+fn parse_term(query: &str, token: &Token) -> Result<Term, TerminalError> {
+    let text_start = token.span.start + usize::from(token.negated) + usize::from(token.quoted);
+    let term = |condition| {
+        Ok(Term {
+            negated: token.negated,
+            condition,
+        })
+    };
+    if token.quoted {
+        return term(Condition::Text(token.text.to_lowercase()));
+    }
+    if let Some(tag) = token.text.strip_prefix('#').filter(|tag| !tag.is_empty()) {
+        return term(Condition::Tag(tag.to_lowercase()));
+    }
+    let Some(at) = token.text.find([':', '<', '>', '=']) else {
+        return term(Condition::Text(token.text.to_lowercase()));
+    };
+
+    let field = &token.text[..at];
+    let (compare, value) = Compare::split(&token.text[at..]);
+    let value_span = text_start + token.text.len() - value.len()..token.span.end;
+    let fail = |reason: String| Err(invalid(query, value_span.clone(), &reason));
+    if value.is_empty() {
+        return fail(format!("falta o valor de '{field}'"));
+    }
+    let equality = || match compare {
+        Compare::Eq => Ok(()),
+        _ => Err(invalid(
+            query,
+            text_start + at..value_span.start,
+            &format!("'{field}' só aceita ':'"),
+        )),
+    };
+
+    match field.to_lowercase().as_str() {
+        "status" | "estado" => {
+            equality()?;
+            match value.to_lowercase().as_str() {
+                "open" | "aberto" | "abertos" | "pendente" => term(Condition::Status(false)),
+                "done" | "resolved" | "resolvido" | "resolvidos" | "feito" => {
+                    term(Condition::Status(true))
+                }
+                _ => fail(format!("status desconhecido '{value}' (use open ou done)")),
+            }
+        }
+        "tag" => {
+            equality()?;
+            let tag = value.trim_start_matches('#');
+            match tag.is_empty() {
+                true => fail("tag vazia".to_string()),
+                false => term(Condition::Tag(tag.to_lowercase())),
+            }
+        }
+        "due" | "prazo" => match Day::parse(value) {
+            Some(day) if day.checked(Local::now().date_naive()).is_none() => {
+                fail(format!("data fora do intervalo '{value}'"))
+            }
+            Some(day) => term(Condition::Due(compare, day)),
+            None => fail(format!(
+                "data desconhecida '{value}' (use AAAA-MM-DD, hoje, amanhã, 7d ou 2w)"
+            )),
+        },
+        "priority" | "prioridade" => match Priority::parse(value) {
+            Some(priority) => term(Condition::Priority(compare, priority)),
+            None => fail(format!(
+                "prioridade desconhecida '{value}' (use low, medium ou high)"
+            )),
+        },
+        _ => Err(TerminalError::UnknownField {
+            query: query.to_string(),
+            span: text_start..text_start + at,
+            field: field.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn todo(message: &str, resolved: bool) -> Todo {
+        let mut todo = Todo::new(message.to_string());
+        todo.resolved = resolved;
+        todo
+    }
+
+    #[test]
+    fn test_query_combines_conditions() {
+        let today = NaiveDate::from_ymd_opt(2023, 3, 1).unwrap();
+        let query =
+            Query::parse(r#"status:open tag:work due<7d priority>=high -tag:someday "invoice""#)
+                .unwrap();
+
+        assert!(query.matches(
+            &todo("send invoice #work !high due:2023-03-05", false),
+            today
+        ));
+        assert!(!query.matches(
+            &todo("send invoice #work !high due:2023-03-08", false),
+            today
+        ));
+        assert!(!query.matches(
+            &todo("send invoice #work !medium due:2023-03-05", false),
+            today
+        ));
+        assert!(!query.matches(
+            &todo("send invoice #work #someday !high due:2023-03-05", false),
+            today
+        ));
+        assert!(!query.matches(
+            &todo("send invoice #work !high due:2023-03-05", true),
+            today
+        ));
+        assert!(!query.matches(
+            &todo("send report #work !high due:2023-03-05", false),
+            today
+        ));
+        assert!(Query::parse("  ").unwrap().is_empty());
+
+        let (lookup, rest) = query.plan(today);
+        assert!(matches!(lookup, Lookup::Open));
+        assert_eq!(rest.terms.len(), 5);
+        let (lookup, rest) = Query::parse("-#casa due<=hoje").unwrap().plan(today);
+        assert!(matches!(lookup, Lookup::DueBy(day) if day == today));
+        assert_eq!(rest.terms.len(), 1);
+    }
+
+    #[test]
+    fn test_far_dates_do_not_overflow() {
+        let query = Query::parse("due<99999d").unwrap();
+        assert!(query.matches(&todo("renovar #casa due:2030-01-01", false), NaiveDate::MAX));
+        assert!(Query::parse("due<-99999d")
+            .unwrap()
+            .terms
+            .iter()
+            .all(|term| term.lookup(NaiveDate::MIN).is_none()));
+    }
+
+    #[test]
+    fn test_parse_errors_point_at_the_problem() {
+        let error = |query| Query::parse(query).err().unwrap().message_err();
+
+        assert_eq!(
+            error("tag:casa priority>=urgente"),
+            "❗ Filtro inválido: prioridade desconhecida 'urgente' (use low, medium ou high) ❗\n    tag:casa priority>=urgente\n                       ^^^^^^^"
+        );
+        assert_eq!(
+            error("-owner:ana"),
+            "❗ Campo desconhecido 'owner' (use status, tag, due, priority ou texto) ❗\n    -owner:ana\n     ^^^^^"
+        );
+        assert!(error("tag<casa").contains("'tag' só aceita ':'"));
+        assert!(error("due:ontem").contains("data desconhecida 'ontem'"));
+        assert!(error("due<999999999d").contains("data fora do intervalo '999999999d'"));
+        assert!(error("due>=-9223372036854775807w").contains("data fora do intervalo"));
+        assert!(error(r#"tag:casa "sem fim"#).contains("aspas sem fechamento"));
+        assert!(error("status:").contains("falta o valor de 'status'"));
+        assert!(error("leite -").contains("condição vazia"));
+    }
+}
@@ -0,0 +1,55 @@
+use crate::crypto::Codec;
+use crate::error::StorageError;
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+
+pub struct RejectedLine {
+    pub file: String,
+    pub line: usize,
+    pub content: String,
+    pub reason: String,
+}
+
+#[derive(Default)]
+pub struct LoadReport {
+    pub created: bool,
+    pub rejected: Vec<RejectedLine>,
+    pub quarantine: Option<String>,
+}
+
+pub struct Quarantine;
+
+impl Quarantine {
+    pub fn path(todo_path: &str) -> String {
+        format!("{todo_path}.rejected")
+    }
+
+    pub async fn store(
+        todo_path: &str,
+        rejected: &[RejectedLine],
+        codec: &Codec,
+    ) -> Result<String, StorageError> {
+        let lines = rejected
+            .iter()
+            .map(|rejected| {
+                format!(
+                    "# {}:{} {}\n{}\n",
+                    rejected.file, rejected.line, rejected.reason, rejected.content
+                )
+            })
+            .collect::<String>();
+
+        let path = Self::path(todo_path);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await
+            .map_err(|err| StorageError::io(&path, err))?;
+        let lines = codec.encode(&lines).map_err(|err| err.at(&path))?;
+        file.write_all(lines.as_bytes())
+            .await
+            .map_err(|err| StorageError::io(&path, err))?;
+        Ok(path)
+    }
+}
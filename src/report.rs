@@ -0,0 +1,271 @@
+use crate::summary::{day_of, view_queries};
+use crate::terminal::TerminalError;
+use crate::todo::{Stamps, Todo};
+use crate::views::View;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Grouping {
+    #[default]
+    Tag,
+    View,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Format {
+    #[default]
+    Markdown,
+    Text,
+    Html,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Text => "txt",
+            Self::Html => "html",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct Options {
+    pub grouping: Grouping,
+    pub format: Format,
+}
+
+impl Options {
+    pub fn parse(input: &str) -> Result<Self, TerminalError> {
+        let mut options = Self::default();
+        for word in input
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|word| !word.is_empty())
+        {
+            match word.to_lowercase().as_str() {
+                "tag" | "tags" => options.grouping = Grouping::Tag,
+                "visão" | "visao" | "visões" | "visoes" | "lista" | "view" => {
+                    options.grouping = Grouping::View
+                }
+                "md" | "markdown" => options.format = Format::Markdown,
+                "txt" | "texto" | "text" => options.format = Format::Text,
+                "html" => options.format = Format::Html,
+                _ => {
+                    return Err(TerminalError::InvalidArgument(format!(
+                        "❗ Opção desconhecida '{word}'. Agrupe por tag ou visão e use md, txt ou html ❗"
+                    )))
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+pub struct Group {
+    pub name: String,
+    pub todos: Vec<(u32, Todo)>,
+}
+
+pub struct Report {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub resolved: usize,
+    pub groups: Vec<Group>,
+}
+
+impl Report {
+    pub fn collect<'a>(
+        todos: impl Iterator<Item = (&'a u32, &'a Todo)>,
+        views: &[View],
+        grouping: Grouping,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Self {
+        let lists = view_queries(views);
+        let mut resolved = 0;
+        let mut groups: BTreeMap<String, Vec<(u32, Todo)>> = BTreeMap::new();
+        let mut ungrouped = Vec::new();
+
+        for (key, todo) in todos {
+            let resolved_on = day_of(todo.stamps.resolved);
+            if !todo.resolved
+                || todo.stamps == Stamps::default()
+                || resolved_on < from
+                || resolved_on > to
+            {
+                continue;
+            }
+            resolved += 1;
+            let names = match grouping {
+                Grouping::Tag => todo
+                    .tags()
+                    .into_iter()
+                    .map(|tag| format!("#{tag}"))
+                    .collect::<Vec<String>>(),
+                Grouping::View => lists
+                    .iter()
+                    .filter(|(_, query)| query.matches(todo, to))
+                    .map(|(view, _)| format!("@{}", view.name))
+                    .collect(),
+            };
+            if names.is_empty() {
+                ungrouped.push((*key, todo.clone()));
+            }
+            for name in names {
+                groups.entry(name).or_default().push((*key, todo.clone()));
+            }
+        }
+
+        let fallback = match grouping {
+            Grouping::Tag => "Sem tag",
+            Grouping::View => "Fora das visões",
+        };
+        let mut groups = groups
+            .into_iter()
+            .chain((!ungrouped.is_empty()).then(|| (fallback.to_string(), ungrouped)))
+            .map(|(name, mut todos)| {
+                todos.sort_by(|(a_key, a), (b_key, b)| {
+                    a.stamps
+                        .resolved
+                        .cmp(&b.stamps.resolved)
+                        .then(a_key.cmp(b_key))
+                });
+                Group { name, todos }
+            })
+            .collect::<Vec<Group>>();
+        groups.sort_by_key(|group| group.name == fallback);
+        Self {
+            from,
+            to,
+            resolved,
+            groups,
+        }
+    }
+
+    pub fn path(&self, todo_path: &str, format: Format) -> String {
+        format!(
+            "{todo_path}.relatorio-{}-{}.{}",
+            self.from,
+            self.to,
+            format.extension()
+        )
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        let title = format!(
+            "TODOs resolvidos de {} a {}",
+            self.from.format("%d/%m/%Y"),
+            self.to.format("%d/%m/%Y")
+        );
+        let summary = format!("{} TODO(s) resolvido(s) no período.", self.resolved);
+        let item = |todo: &Todo| {
+            (
+                todo.message.trim().to_string(),
+                day_of(todo.stamps.resolved).format("%d/%m/%Y").to_string(),
+            )
+        };
+
+        let mut out = match format {
+            Format::Markdown => format!("# {title}\n\n{summary}\n"),
+            Format::Text => format!("{title}\n{summary}\n"),
+            Format::Html => format!("<h1>{}</h1>\n<p>{}</p>\n", escape(&title), escape(&summary)),
+        };
+        for group in &self.groups {
+            let heading = format!("{} ({})", group.name, group.todos.len());
+            out.push_str(&match format {
+                Format::Markdown => format!("\n## {heading}\n\n"),
+                Format::Text => format!("\n{heading}\n"),
+                Format::Html => format!("<h2>{}</h2>\n<ul>\n", escape(&heading)),
+            });
+            for (_, todo) in &group.todos {
+                let (message, day) = item(todo);
+                out.push_str(&match format {
+                    Format::Markdown => format!("- {message} — {day}\n"),
+                    Format::Text => format!("  * {message} ({day})\n"),
+                    Format::Html => {
+                        format!("<li>{} <small>{day}</small></li>\n", escape(&message))
+                    }
+                });
+            }
+            if format == Format::Html {
+                out.push_str("</ul>\n");
+            }
+        }
+        out
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn todo(message: &str, resolved: Option<u32>) -> Todo {
+        let at = |day| Utc.with_ymd_and_hms(2023, 3, day, 12, 0, 0).unwrap();
+        let mut todo = Todo::new(message.to_string());
+        todo.stamps = Stamps::at(at(1));
+        if let Some(day) = resolved {
+            todo.resolved = true;
+            todo.stamps.resolved = at(day);
+        }
+        todo
+    }
+
+    #[test]
+    fn test_report_groups_resolved_todos_in_range() {
+        let todos = BTreeMap::from([
+            (1, todo("fatura #trabalho #cliente", Some(6))),
+            (2, todo("relatório #trabalho", Some(3))),
+            (3, todo("leite <integral>", Some(4))),
+            (4, todo("pão #casa", None)),
+            (5, todo("reunião #trabalho", Some(9))),
+            (6, Todo::legacy(6, "antigo".to_string(), true)),
+        ]);
+        let day = |day| NaiveDate::from_ymd_opt(2023, 3, day).unwrap();
+        let report = Report::collect(todos.iter(), &[], Grouping::Tag, day(1), day(7));
+
+        assert_eq!(report.resolved, 3);
+        assert_eq!(
+            report
+                .groups
+                .iter()
+                .map(|group| (group.name.as_str(), group.todos.len()))
+                .collect::<Vec<(&str, usize)>>(),
+            vec![("#cliente", 1), ("#trabalho", 2), ("Sem tag", 1)]
+        );
+        assert_eq!(report.groups[1].todos[0].0, 2);
+        assert_eq!(
+            report.render(Format::Markdown),
+            "# TODOs resolvidos de 01/03/2023 a 07/03/2023\n\n3 TODO(s) resolvido(s) no período.\n\n## #cliente (1)\n\n- fatura #trabalho #cliente — 06/03/2023\n\n## #trabalho (2)\n\n- relatório #trabalho — 03/03/2023\n- fatura #trabalho #cliente — 06/03/2023\n\n## Sem tag (1)\n\n- leite <integral> — 04/03/2023\n"
+        );
+        assert!(report
+            .render(Format::Text)
+            .ends_with("\nSem tag (1)\n  * leite <integral> (04/03/2023)\n"));
+        assert!(report
+            .render(Format::Html)
+            .contains("<li>leite &lt;integral&gt; <small>04/03/2023</small></li>\n</ul>\n"));
+        assert_eq!(
+            report.path("todo.txt", Format::Html),
+            "todo.txt.relatorio-2023-03-01-2023-03-07.html"
+        );
+
+        let views = [View::new("trabalho", "tag:trabalho")];
+        let report = Report::collect(todos.iter(), &views, Grouping::View, day(1), day(31));
+        assert_eq!(report.groups[0].name, "@trabalho");
+        assert_eq!(report.groups[0].todos.len(), 3);
+        assert_eq!(report.groups[1].name, "Fora das visões");
+
+        let options = Options::parse("visão, html").unwrap();
+        assert!(options.grouping == Grouping::View && options.format == Format::Html);
+        assert!(Options::parse("").unwrap() == Options::default());
+        assert!(Options::parse("pdf").is_err());
+    }
+}
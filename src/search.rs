@@ -0,0 +1,193 @@
+use crate::todo::Todo;
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+const NOTES_WEIGHT: i64 = 2;
+
+#[derive(Clone)]
+enum Mode {
+    Substring(Vec<char>),
+    Regex(Regex),
+    Fuzzy(Vec<char>),
+}
+
+#[derive(Clone)]
+pub struct Search {
+    mode: Mode,
+}
+
+pub struct Hit {
+    pub key: u32,
+    pub todo: Todo,
+    pub score: i64,
+}
+
+impl Search {
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let query = query.trim();
+        let mode = match (query.strip_prefix("re:"), query.strip_prefix('~')) {
+            (Some(pattern), _) => RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map(Mode::Regex)
+                .map_err(|err| format!("expressão regular inválida ({err})"))?,
+            (_, Some(pattern)) => Mode::Fuzzy(pattern.chars().map(fold).collect()),
+            _ => Mode::Substring(query.chars().map(fold).collect()),
+        };
+        match &mode {
+            Mode::Substring(needle) | Mode::Fuzzy(needle) if needle.is_empty() => {
+                Err("informe o que deseja buscar".to_string())
+            }
+            _ => Ok(Self { mode }),
+        }
+    }
+
+    pub fn find(&self, text: &str) -> Vec<Range<usize>> {
+        match &self.mode {
+            Mode::Substring(needle) => find_substring(text, needle),
+            Mode::Regex(regex) => regex
+                .find_iter(text)
+                .map(|found| found.range())
+                .filter(|range| !range.is_empty())
+                .collect(),
+            Mode::Fuzzy(needle) => {
+                find_fuzzy(text, needle).map_or_else(Vec::new, |(_, ranges)| ranges)
+            }
+        }
+    }
+
+    pub fn score(&self, todo: &Todo) -> Option<i64> {
+        let score = |text: &str| match &self.mode {
+            Mode::Fuzzy(needle) => find_fuzzy(text, needle).map(|(score, _)| score),
+            _ => {
+                let found = self.find(text);
+                let first = found.first()?.start as i64;
+                Some(100 + 10 * found.len() as i64 - first.min(50))
+            }
+        };
+        match (score(&todo.message), score(&todo.notes)) {
+            (None, None) => None,
+            (message, notes) => Some(message.unwrap_or(0) * NOTES_WEIGHT + notes.unwrap_or(0)),
+        }
+    }
+
+    pub fn rank<'a>(&self, todos: impl Iterator<Item = (&'a u32, &'a Todo)>) -> Vec<Hit> {
+        let mut hits = todos
+            .filter_map(|(key, todo)| {
+                let score = self.score(todo)?;
+                Some(Hit {
+                    key: *key,
+                    todo: todo.clone(),
+                    score,
+                })
+            })
+            .collect::<Vec<Hit>>();
+        hits.sort_by(|a, b| b.score.cmp(&a.score).then(a.key.cmp(&b.key)));
+        hits
+    }
+}
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn find_substring(text: &str, needle: &[char]) -> Vec<Range<usize>> {
+    let chars = text.char_indices().collect::<Vec<(usize, char)>>();
+    let mut found = Vec::new();
+    let mut start = 0;
+    while start + needle.len() <= chars.len() {
+        let matches = chars[start..start + needle.len()]
+            .iter()
+            .zip(needle)
+            .all(|((_, c), n)| fold(*c) == *n);
+        if !matches {
+            start += 1;
+            continue;
+        }
+        let (last, c) = chars[start + needle.len() - 1];
+        found.push(chars[start].0..last + c.len_utf8());
+        start += needle.len();
+    }
+    found
+}
+
+fn find_fuzzy(text: &str, needle: &[char]) -> Option<(i64, Vec<Range<usize>>)> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut score = 0;
+    let mut wanted = needle.iter().peekable();
+    let mut previous: Option<char> = None;
+    let mut gap = 0;
+    for (at, c) in text.char_indices() {
+        let Some(next) = wanted.peek() else {
+            break;
+        };
+        if fold(c) != **next {
+            gap += 1;
+            previous = Some(c);
+            continue;
+        }
+        wanted.next();
+        score += 10;
+        match ranges.last_mut() {
+            Some(range) if range.end == at => {
+                score += 15;
+                range.end = at + c.len_utf8();
+            }
+            _ => ranges.push(at..at + c.len_utf8()),
+        }
+        if !matches!(previous, Some(previous) if previous.is_alphanumeric()) {
+            score += 10;
+        }
+        score -= gap.min(5);
+        gap = 0;
+        previous = Some(c);
+    }
+    wanted.peek().is_none().then_some((score, ranges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn todo(message: &str, notes: &str) -> Todo {
+        let mut todo = Todo::new(message.to_string());
+        todo.notes = notes.to_string();
+        todo
+    }
+
+    #[test]
+    fn test_substring_is_case_insensitive_and_ranked() {
+        let search = Search::parse("Leite").unwrap();
+        assert_eq!(search.find("comprar LEITE e leite"), vec![8..13, 16..21]);
+        assert_eq!(search.find("pão"), Vec::<Range<usize>>::new());
+
+        let todos = BTreeMap::from([
+            (1, todo("pão", "sem leite")),
+            (2, todo("leite", "")),
+            (3, todo("ovos", "")),
+        ]);
+        let hits = search.rank(todos.iter());
+        assert_eq!(
+            hits.iter().map(|hit| hit.key).collect::<Vec<u32>>(),
+            vec![2, 1]
+        );
+    }
+
+    #[test]
+    fn test_regex_and_fuzzy_modes() {
+        let search = Search::parse("re:p[aã]o").unwrap();
+        assert_eq!(search.find("PÃO e pao"), vec![0..4, 7..10]);
+        assert!(Search::parse("re:(").is_err());
+        assert!(Search::parse("  ").is_err());
+
+        let search = Search::parse("~rlt").unwrap();
+        assert!(search.score(&todo("relatório", "")).is_some());
+        assert!(search.score(&todo("roteiro", "")).is_none());
+        assert_eq!(search.find("relatório"), vec![0..1, 2..3, 4..5]);
+
+        let todos = BTreeMap::from([(1, todo("rolo de lata", "")), (2, todo("relatório", ""))]);
+        let hits = search.rank(todos.iter());
+        assert_eq!(hits[0].key, 2);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::todo::Todo;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, Timelike, Weekday};
+
+const UPCOMING_DAYS: i64 = 7;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Calendar {
+    pub day_start: NaiveTime,
+    pub week_start: Weekday,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self {
+            day_start: NaiveTime::MIN,
+            week_start: Weekday::Mon,
+        }
+    }
+}
+
+impl Calendar {
+    pub fn today(&self, now: DateTime<Local>) -> NaiveDate {
+        (now.naive_local() - Duration::seconds(self.day_start.num_seconds_from_midnight().into()))
+            .date()
+    }
+
+    pub fn week_end(&self, today: NaiveDate) -> NaiveDate {
+        let elapsed = today.weekday().days_since(self.week_start);
+        today + Duration::days(6 - i64::from(elapsed))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SmartList {
+    Overdue,
+    Today,
+    Week,
+    Upcoming,
+    Undated,
+}
+
+impl SmartList {
+    pub const ALL: [Self; 5] = [
+        Self::Overdue,
+        Self::Today,
+        Self::Week,
+        Self::Upcoming,
+        Self::Undated,
+    ];
+
+    pub fn shortcut(self) -> &'static str {
+        match self {
+            Self::Overdue => "2a",
+            Self::Today => "2h",
+            Self::Week => "2s",
+            Self::Upcoming => "2p",
+            Self::Undated => "2d",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Overdue => "atrasados",
+            Self::Today => "hoje",
+            Self::Week => "semana",
+            Self::Upcoming => "próximos",
+            Self::Undated => "sem-data",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Overdue => "Atrasados",
+            Self::Today => "Hoje",
+            Self::Week => "Esta semana",
+            Self::Upcoming => "Próximos 7 dias",
+            Self::Undated => "Sem data",
+        }
+    }
+
+    pub fn matches(self, todo: &Todo, today: NaiveDate, calendar: &Calendar) -> bool {
+        if todo.resolved {
+            return false;
+        }
+        let due = todo.due();
+        let scheduled = todo.scheduled();
+        let overdue = due.is_some_and(|due| due < today);
+        let ahead = |last: NaiveDate| {
+            [due, scheduled]
+                .into_iter()
+                .flatten()
+                .any(|day| day > today && day <= last)
+        };
+        match self {
+            Self::Overdue => overdue,
+            Self::Today => {
+                !overdue
+                    && (due == Some(today) || scheduled.is_some_and(|scheduled| scheduled <= today))
+            }
+            Self::Week => ahead(calendar.week_end(today)),
+            Self::Upcoming => ahead(today + Duration::days(UPCOMING_DAYS)),
+            Self::Undated => due.is_none() && scheduled.is_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn todo(message: &str) -> Todo {
+        Todo::new(message.to_string())
+    }
+
+    #[test]
+    fn test_smart_lists_follow_the_calendar() {
+        let calendar = Calendar {
+            day_start: NaiveTime::from_hms_opt(4, 0, 0).unwrap(),
+            week_start: Weekday::Sun,
+        };
+        let late_night = Local.with_ymd_and_hms(2023, 3, 2, 2, 30, 0).unwrap();
+        let morning = Local.with_ymd_and_hms(2023, 3, 2, 9, 0, 0).unwrap();
+        let day = |day| NaiveDate::from_ymd_opt(2023, 3, day).unwrap();
+        assert_eq!(calendar.today(late_night), day(1));
+        assert_eq!(calendar.today(morning), day(2));
+        assert_eq!(Calendar::default().today(late_night), day(2));
+        assert_eq!(calendar.week_end(day(2)), day(4));
+        assert_eq!(Calendar::default().week_end(day(2)), day(5));
+        assert_eq!(Calendar::default().week_end(day(5)), day(5));
+
+        let today = calendar.today(morning);
+        let lists = |message: &str| {
+            SmartList::ALL
+                .into_iter()
+                .filter(|list| list.matches(&todo(message), today, &calendar))
+                .map(SmartList::name)
+                .collect::<Vec<&str>>()
+        };
+        assert_eq!(lists("pagar luz due:2023-03-01"), vec!["atrasados"]);
+        assert_eq!(lists("enviar fatura due:2023-03-02"), vec!["hoje"]);
+        assert_eq!(
+            lists("ligar banco scheduled:2023-02-28 due:2023-03-06"),
+            vec!["hoje", "próximos"]
+        );
+        assert_eq!(
+            lists("revisar PR due:2023-03-04"),
+            vec!["semana", "próximos"]
+        );
+        assert_eq!(lists("viagem agendado:2023-03-20"), Vec::<&str>::new());
+        assert_eq!(lists("comprar pão"), vec!["sem-data"]);
+
+        let mut resolved = todo("pagar luz due:2023-03-01");
+        resolved.resolved = true;
+        assert!(!SmartList::Overdue.matches(&resolved, today, &calendar));
+    }
+}
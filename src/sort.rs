@@ -0,0 +1,205 @@
+use crate::terminal::TerminalError;
+use crate::todo::Todo;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter, Result};
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum SortKey {
+    #[default]
+    Manual,
+    Created,
+    Due,
+    Priority,
+    Alphabetical,
+    Status,
+}
+
+impl SortKey {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "manual" | "posição" | "posicao" => Some(Self::Manual),
+            "criado" | "criação" | "criacao" | "created" => Some(Self::Created),
+            "prazo" | "due" => Some(Self::Due),
+            "prioridade" | "priority" => Some(Self::Priority),
+            "alfabetica" | "alfabética" | "a-z" | "alphabetical" => Some(Self::Alphabetical),
+            "status" | "estado" => Some(Self::Status),
+            _ => None,
+        }
+    }
+
+    fn compare(self, a: (u32, &Todo), b: (u32, &Todo), descending: bool) -> Ordering {
+        match self {
+            Self::Manual => present(Some(a.1.place(a.0)), Some(b.1.place(b.0)), descending),
+            Self::Created => present(
+                Some(a.1.stamps.created),
+                Some(b.1.stamps.created),
+                descending,
+            ),
+            Self::Due => present(a.1.due(), b.1.due(), descending),
+            Self::Priority => present(a.1.priority(), b.1.priority(), descending),
+            Self::Alphabetical => present(
+                Some(a.1.message.trim().to_lowercase()),
+                Some(b.1.message.trim().to_lowercase()),
+                descending,
+            ),
+            Self::Status => present(Some(a.1.resolved), Some(b.1.resolved), descending),
+        }
+    }
+}
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let name = match self {
+            Self::Manual => "manual",
+            Self::Created => "criado",
+            Self::Due => "prazo",
+            Self::Priority => "prioridade",
+            Self::Alphabetical => "alfabética",
+            Self::Status => "status",
+        };
+        write!(f, "{name}")
+    }
+}
+
+fn present<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Move {
+    Up,
+    Down,
+    Top,
+    Bottom,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Sort {
+    pub primary: SortKey,
+    #[serde(default)]
+    pub secondary: Option<SortKey>,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+impl Sort {
+    pub fn parse(input: &str, current: Sort) -> std::result::Result<Self, TerminalError> {
+        let mut keys = Vec::new();
+        let mut descending = None;
+        let mut toggle = false;
+        for word in input
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|word| !word.is_empty())
+        {
+            match word.to_lowercase().as_str() {
+                "asc" | "crescente" => descending = Some(false),
+                "desc" | "decrescente" => descending = Some(true),
+                "inverter" | "!" => toggle = !toggle,
+                name => keys.push(SortKey::parse(name).ok_or_else(|| {
+                    TerminalError::InvalidArgument(format!(
+                        "❗ Ordem desconhecida '{word}'. Use manual, criado, prazo, prioridade, alfabetica ou status ❗"
+                    ))
+                })?),
+            }
+        }
+
+        let mut sort = match keys.as_slice() {
+            [] => current,
+            [primary] => Self {
+                primary: *primary,
+                ..Self::default()
+            },
+            [primary, secondary] => Self {
+                primary: *primary,
+                secondary: Some(*secondary).filter(|secondary| secondary != primary),
+                ..Self::default()
+            },
+            _ => {
+                return Err(TerminalError::InvalidArgument(
+                    "❗ Informe no máximo duas ordens (principal e secundária) ❗".to_string(),
+                ))
+            }
+        };
+        sort.descending = descending.unwrap_or(sort.descending) != toggle;
+        Ok(sort)
+    }
+
+    pub fn is_manual(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn compare(&self, a: (u32, &Todo), b: (u32, &Todo)) -> Ordering {
+        b.1.pinned
+            .cmp(&a.1.pinned)
+            .then_with(|| self.primary.compare(a, b, self.descending))
+            .then_with(|| {
+                self.secondary
+                    .map_or(Ordering::Equal, |key| key.compare(a, b, self.descending))
+            })
+            .then(a.0.cmp(&b.0))
+    }
+}
+
+impl Display for Sort {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.primary)?;
+        if let Some(secondary) = self.secondary {
+            write!(f, ", {secondary}")?;
+        }
+        write!(f, " {}", if self.descending { "↓" } else { "↑" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(sort: &Sort, todos: &[Todo]) -> Vec<u32> {
+        let mut keys = (1..=todos.len() as u32).collect::<Vec<u32>>();
+        keys.sort_by(|a, b| {
+            sort.compare((*a, &todos[*a as usize - 1]), (*b, &todos[*b as usize - 1]))
+        });
+        keys
+    }
+
+    #[test]
+    fn test_sort_by_primary_and_secondary_keys() {
+        let todos = [
+            Todo::new("pagar luz !high due:2023-03-10".to_string()),
+            Todo::new("comprar pão".to_string()),
+            Todo::new("Arrumar casa !low due:2023-03-10".to_string()),
+            Todo::new("enviar fatura !high due:2023-03-01".to_string()),
+        ];
+
+        let sort = Sort::parse("prazo, prioridade", Sort::default()).unwrap();
+        assert_eq!(sorted(&sort, &todos), vec![4, 3, 1, 2]);
+        let sort = Sort::parse("inverter", sort).unwrap();
+        assert!(sort.descending);
+        assert_eq!(sorted(&sort, &todos), vec![1, 3, 4, 2]);
+        assert_eq!(sort.to_string(), "prazo, prioridade ↓");
+
+        let sort = Sort::parse("alfabetica", sort).unwrap();
+        assert!(!sort.descending);
+        assert_eq!(sorted(&sort, &todos), vec![3, 2, 4, 1]);
+        assert_eq!(
+            sorted(&Sort::parse("prioridade desc", sort).unwrap(), &todos),
+            vec![1, 4, 3, 2]
+        );
+        assert!(Sort::parse("", Sort::default()).unwrap().is_manual());
+        assert!(Sort::parse("tamanho", Sort::default()).is_err());
+        assert!(Sort::parse("prazo status manual", Sort::default()).is_err());
+
+        let mut todos = todos;
+        todos[1].pinned = true;
+        todos[3].position = Some(0);
+        assert_eq!(sorted(&Sort::default(), &todos), vec![2, 4, 1, 3]);
+        assert_eq!(sorted(&sort, &todos), vec![2, 3, 4, 1]);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::summary::{day_of, view_queries};
+use crate::todo::{Stamps, Todo};
+use crate::views::View;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::BTreeMap;
+
+const DAYS: usize = 30;
+const WEEKS: usize = 8;
+const OLDEST: usize = 5;
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub struct Breakdown {
+    pub name: String,
+    pub open: usize,
+    pub resolved: usize,
+}
+
+impl Breakdown {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            open: 0,
+            resolved: 0,
+        }
+    }
+
+    fn count(&mut self, todo: &Todo) {
+        match todo.resolved {
+            true => self.resolved += 1,
+            false => self.open += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.open + self.resolved
+    }
+}
+
+pub struct Stats {
+    pub open: usize,
+    pub resolved: usize,
+    pub completions: Vec<usize>,
+    pub rates: Vec<f64>,
+    pub time_to_resolve: Option<Duration>,
+    pub oldest: Vec<(u32, Todo)>,
+    pub tags: Vec<Breakdown>,
+    pub lists: Vec<Breakdown>,
+}
+
+impl Stats {
+    pub fn collect<'a>(
+        todos: impl Iterator<Item = (&'a u32, &'a Todo)>,
+        views: &[View],
+        now: DateTime<Utc>,
+    ) -> Self {
+        let today = day_of(now);
+        let first_day = today - Duration::days(DAYS as i64 - 1);
+        let week_ends = (0..WEEKS)
+            .rev()
+            .map(|weeks| today - Duration::weeks(weeks as i64))
+            .collect::<Vec<NaiveDate>>();
+        let lists = view_queries(views);
+
+        let mut stats = Self {
+            open: 0,
+            resolved: 0,
+            completions: vec![0; DAYS],
+            rates: Vec::new(),
+            time_to_resolve: None,
+            oldest: Vec::new(),
+            tags: Vec::new(),
+            lists: lists
+                .iter()
+                .map(|(view, _)| Breakdown::new(&view.name))
+                .collect(),
+        };
+        let mut created = [0; WEEKS];
+        let mut finished = [0; WEEKS];
+        let mut waited = Duration::zero();
+        let mut measured = 0;
+        let mut tags: BTreeMap<String, Breakdown> = BTreeMap::new();
+
+        for (key, todo) in todos {
+            let stamped = todo.stamps != Stamps::default();
+            match todo.resolved {
+                true => stats.resolved += 1,
+                false => stats.open += 1,
+            }
+            for tag in todo.tags() {
+                tags.entry(tag.clone())
+                    .or_insert_with(|| Breakdown::new(&tag))
+                    .count(todo);
+            }
+            for ((_, query), list) in lists.iter().zip(stats.lists.iter_mut()) {
+                if query.matches(todo, today) {
+                    list.count(todo);
+                }
+            }
+            if !stamped {
+                continue;
+            }
+
+            let created_on = day_of(todo.stamps.created);
+            for (week, end) in week_ends.iter().enumerate() {
+                if created_on <= *end {
+                    created[week] += 1;
+                }
+            }
+            if !todo.resolved {
+                stats.oldest.push((*key, todo.clone()));
+                continue;
+            }
+            let resolved_on = day_of(todo.stamps.resolved);
+            for (week, end) in week_ends.iter().enumerate() {
+                if resolved_on <= *end {
+                    finished[week] += 1;
+                }
+            }
+            if resolved_on >= first_day && resolved_on <= today {
+                stats.completions[(resolved_on - first_day).num_days() as usize] += 1;
+            }
+            if todo.stamps.resolved > todo.stamps.created {
+                waited += todo.stamps.resolved - todo.stamps.created;
+                measured += 1;
+            }
+        }
+
+        stats.rates = created
+            .iter()
+            .zip(&finished)
+            .map(|(created, finished)| match created {
+                0 => 0.0,
+                created => *finished as f64 / *created as f64,
+            })
+            .collect();
+        stats.time_to_resolve = (measured > 0).then(|| waited / measured);
+        stats.oldest.sort_by(|(a_key, a), (b_key, b)| {
+            a.stamps
+                .created
+                .cmp(&b.stamps.created)
+                .then(a_key.cmp(b_key))
+        });
+        stats.oldest.truncate(OLDEST);
+        stats.tags = tags.into_values().collect();
+        stats
+            .tags
+            .sort_by(|a, b| b.total().cmp(&a.total()).then(a.name.cmp(&b.name)));
+        stats
+    }
+
+    pub fn total(&self) -> usize {
+        self.open + self.resolved
+    }
+
+    pub fn rate(&self) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.resolved as f64 / total as f64,
+        }
+    }
+}
+
+pub fn sparkline(values: &[f64], max: f64) -> String {
+    values
+        .iter()
+        .map(|value| match max > 0.0 {
+            true => {
+                let level = (value / max * (SPARKS.len() - 1) as f64).round() as usize;
+                SPARKS[level.min(SPARKS.len() - 1)]
+            }
+            false => SPARKS[0],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn todo(message: &str, created: u32, resolved: Option<u32>) -> Todo {
+        let at = |day| Utc.with_ymd_and_hms(2023, 3, day, 12, 0, 0).unwrap();
+        let mut todo = Todo::new(message.to_string());
+        todo.stamps = Stamps::at(at(created));
+        if let Some(day) = resolved {
+            todo.resolved = true;
+            todo.stamps.resolved = at(day);
+        }
+        todo
+    }
+
+    #[test]
+    fn test_stats_summarize_the_list() {
+        let todos = BTreeMap::from([
+            (1, todo("relatório #trabalho", 1, Some(3))),
+            (2, todo("fatura #trabalho", 2, Some(30))),
+            (3, todo("leite #casa", 10, None)),
+            (4, todo("pão #casa", 5, None)),
+            (5, Todo::legacy(5, "antigo".to_string(), false)),
+        ]);
+        let views = [
+            View::new("trabalho", "tag:trabalho"),
+            View::new("quebrada", "prioridade:urgente"),
+        ];
+        let now = Utc.with_ymd_and_hms(2023, 3, 30, 18, 0, 0).unwrap();
+        let stats = Stats::collect(todos.iter(), &views, now);
+
+        assert_eq!((stats.total(), stats.open, stats.resolved), (5, 3, 2));
+        assert_eq!(stats.rate(), 0.4);
+        assert_eq!(stats.completions.len(), 30);
+        assert_eq!(stats.completions[29], 1);
+        assert_eq!(stats.completions.iter().sum::<usize>(), 2);
+        assert_eq!(stats.rates.len(), 8);
+        assert_eq!(stats.rates[7], 0.5);
+        assert_eq!(stats.time_to_resolve, Some(Duration::days(15)));
+        assert_eq!(
+            stats
+                .oldest
+                .iter()
+                .map(|(key, _)| *key)
+                .collect::<Vec<u32>>(),
+            vec![4, 3]
+        );
+        assert_eq!(stats.tags[0].name, "casa");
+        assert_eq!((stats.tags[1].open, stats.tags[1].resolved), (0, 2));
+        assert_eq!(stats.lists.len(), 1);
+        assert_eq!(stats.lists[0].total(), 2);
+
+        assert_eq!(sparkline(&[0.0, 1.0, 2.0, 4.0], 4.0), "▁▃▅█");
+        assert_eq!(sparkline(&[0.0, 0.0], 0.0), "▁▁");
+    }
+}
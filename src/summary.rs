@@ -0,0 +1,14 @@
+use crate::query::Query;
+use crate::views::View;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+
+pub fn day_of(moment: DateTime<Utc>) -> NaiveDate {
+    moment.with_timezone(&Local).date_naive()
+}
+
+pub fn view_queries(views: &[View]) -> Vec<(&View, Query)> {
+    views
+        .iter()
+        .filter_map(|view| Some((view, Query::parse(&view.query).ok()?)))
+        .collect()
+}
@@ -0,0 +1,286 @@
+use crate::error::StorageError;
+use crate::todo::Todo;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+pub enum Resolution {
+    Mine,
+    Theirs,
+    Both,
+}
+
+pub struct Conflict {
+    pub mine: Option<Todo>,
+    pub theirs: Option<Todo>,
+}
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait SyncStorage {
+    async fn plan_sync(&mut self, path: &str, other: &str) -> Result<Merge, StorageError>;
+    async fn apply_sync(
+        &mut self,
+        path: &str,
+        other: &str,
+        merge: Merge,
+    ) -> Result<u32, StorageError>;
+}
+
+#[derive(Default)]
+pub struct Merge {
+    pub todos: Vec<Todo>,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Merge {
+    pub fn base_path(todo_path: &str) -> String {
+        format!("{todo_path}.sync-base")
+    }
+
+    pub fn new(
+        base: &BTreeMap<u32, Todo>,
+        mine: &BTreeMap<u32, Todo>,
+        theirs: &BTreeMap<u32, Todo>,
+    ) -> Self {
+        let base = by_id(base);
+        let their_ids = by_id(theirs);
+        let mut merge = Merge::default();
+
+        for todo in mine.values() {
+            match (base.get(todo.id.as_str()), their_ids.get(todo.id.as_str())) {
+                (base, Some(theirs)) => match merge_todo(base.copied(), todo, theirs) {
+                    Some(merged) => merge.todos.push(merged),
+                    None => merge.conflict(Some(todo), Some(theirs)),
+                },
+                (None, None) => merge.todos.push(todo.clone()),
+                (Some(base), None) if same(base, todo) => {}
+                (Some(_), None) => merge.conflict(Some(todo), None),
+            }
+        }
+
+        let my_ids = by_id(mine);
+        for todo in theirs
+            .values()
+            .filter(|todo| !my_ids.contains_key(todo.id.as_str()))
+        {
+            match base.get(todo.id.as_str()) {
+                None => merge.todos.push(todo.clone()),
+                Some(base) if same(base, todo) => {}
+                Some(_) => merge.conflict(None, Some(todo)),
+            }
+        }
+        merge
+    }
+
+    pub fn resolve(&mut self, conflict: Conflict, resolution: Resolution) {
+        match (resolution, conflict.mine, conflict.theirs) {
+            (Resolution::Mine, mine, _) => self.todos.extend(mine),
+            (Resolution::Theirs, _, theirs) => self.todos.extend(theirs),
+            (Resolution::Both, Some(mine), Some(mut theirs)) => {
+                theirs.id = Uuid::new_v4().simple().to_string();
+                self.todos.push(mine);
+                self.todos.push(theirs);
+            }
+            (Resolution::Both, mine, theirs) => self.todos.extend(mine.or(theirs)),
+        }
+    }
+
+    fn conflict(&mut self, mine: Option<&Todo>, theirs: Option<&Todo>) {
+        self.conflicts.push(Conflict {
+            mine: mine.cloned(),
+            theirs: theirs.cloned(),
+        });
+    }
+}
+
+fn by_id(todos: &BTreeMap<u32, Todo>) -> HashMap<&str, &Todo> {
+    todos
+        .values()
+        .map(|todo| (todo.id.as_str(), todo))
+        .collect()
+}
+
+fn same(a: &Todo, b: &Todo) -> bool {
+    a.message == b.message
+        && a.notes == b.notes
+        && a.resolved == b.resolved
+        && a.position == b.position
+        && a.pinned == b.pinned
+}
+
+fn merge_todo(base: Option<&Todo>, mine: &Todo, theirs: &Todo) -> Option<Todo> {
+    let (message, message_at) = pick(
+        base.map(|todo| &todo.message),
+        (&mine.message, mine.stamps.message),
+        (&theirs.message, theirs.stamps.message),
+    )?;
+    let (notes, notes_at) = pick(
+        base.map(|todo| &todo.notes),
+        (&mine.notes, mine.stamps.notes),
+        (&theirs.notes, theirs.stamps.notes),
+    )?;
+    let (resolved, resolved_at) = pick(
+        base.map(|todo| &todo.resolved),
+        (&mine.resolved, mine.stamps.resolved),
+        (&theirs.resolved, theirs.stamps.resolved),
+    )?;
+    let (position, position_at) = pick(
+        base.map(|todo| &todo.position),
+        (&mine.position, mine.stamps.position),
+        (&theirs.position, theirs.stamps.position),
+    )?;
+    let (pinned, pinned_at) = pick(
+        base.map(|todo| &todo.pinned),
+        (&mine.pinned, mine.stamps.pinned),
+        (&theirs.pinned, theirs.stamps.pinned),
+    )?;
+
+    let mut merged = mine.clone();
+    merged.message = message.clone();
+    merged.notes = notes.clone();
+    merged.resolved = *resolved;
+    merged.position = *position;
+    merged.pinned = *pinned;
+    merged.stamps.message = message_at;
+    merged.stamps.notes = notes_at;
+    merged.stamps.resolved = resolved_at;
+    merged.stamps.position = position_at;
+    merged.stamps.pinned = pinned_at;
+    merged.stamps.created = mine.stamps.created.min(theirs.stamps.created);
+    Some(merged)
+}
+
+fn pick<'a, T: PartialEq>(
+    base: Option<&T>,
+    mine: (&'a T, DateTime<Utc>),
+    theirs: (&'a T, DateTime<Utc>),
+) -> Option<(&'a T, DateTime<Utc>)> {
+    match base {
+        _ if mine.0 == theirs.0 => Some((mine.0, mine.1.max(theirs.1))),
+        Some(base) if base == mine.0 => Some(theirs),
+        Some(base) if base == theirs.0 => Some(mine),
+        Some(_) => None,
+        None if mine.1 >= theirs.1 => Some(mine),
+        None => Some(theirs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::Stamps;
+    use chrono::{Datelike, TimeZone};
+
+    fn todo(id: &str, message: &str, resolved: bool, day: u32) -> Todo {
+        let mut todo = Todo::new(message.to_string());
+        todo.id = id.to_string();
+        todo.resolved = resolved;
+        todo.stamps = Stamps::at(Utc.with_ymd_and_hms(2023, 1, day, 12, 0, 0).unwrap());
+        todo
+    }
+
+    fn store(todos: &[Todo]) -> BTreeMap<u32, Todo> {
+        todos
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(key, todo)| (key as u32 + 1, todo))
+            .collect()
+    }
+
+    #[test]
+    fn test_independent_edits_are_merged() {
+        let base = store(&[todo("a", "milk", false, 1), todo("b", "bread", false, 1)]);
+        let mine = store(&[
+            todo("a", "milk", true, 2),
+            todo("b", "bread", false, 1),
+            todo("c", "eggs", false, 2),
+        ]);
+        let theirs = store(&[todo("a", "oat milk", false, 3)]);
+
+        let merge = Merge::new(&base, &mine, &theirs);
+        assert!(merge.conflicts.is_empty());
+        assert_eq!(merge.todos.len(), 2);
+        assert_eq!(merge.todos[0].message, "oat milk");
+        assert!(merge.todos[0].resolved);
+        assert_eq!(merge.todos[1].id, "c");
+    }
+
+    #[test]
+    fn test_concurrent_edits_conflict() {
+        let base = store(&[todo("a", "milk", false, 1), todo("b", "bread", false, 1)]);
+        let mine = store(&[
+            todo("a", "soy milk", false, 2),
+            todo("b", "rye bread", false, 2),
+        ]);
+        let theirs = store(&[todo("a", "oat milk", false, 3)]);
+
+        let mut merge = Merge::new(&base, &mine, &theirs);
+        assert_eq!(merge.conflicts.len(), 2);
+        assert!(merge.todos.is_empty());
+
+        let mut conflicts = std::mem::take(&mut merge.conflicts).into_iter();
+        merge.resolve(conflicts.next().unwrap(), Resolution::Both);
+        merge.resolve(conflicts.next().unwrap(), Resolution::Theirs);
+        assert_eq!(merge.todos.len(), 2);
+        assert_eq!(merge.todos[0].message, "soy milk");
+        assert_eq!(merge.todos[1].message, "oat milk");
+        assert_ne!(merge.todos[1].id, "a");
+    }
+
+    #[test]
+    fn test_notes_are_merged_and_kept_on_delete() {
+        let base = store(&[todo("a", "milk", false, 1), todo("b", "bread", false, 1)]);
+        let mine = store(&[todo("a", "milk", true, 2)]);
+        let mut oat = todo("a", "milk", false, 1);
+        oat.notes = "oat".to_string();
+        oat.stamps.notes = Utc.with_ymd_and_hms(2023, 1, 3, 12, 0, 0).unwrap();
+        let mut rye = todo("b", "bread", false, 1);
+        rye.notes = "rye".to_string();
+        let theirs = store(&[oat, rye]);
+
+        let merge = Merge::new(&base, &mine, &theirs);
+        assert_eq!(merge.todos.len(), 1);
+        assert_eq!(merge.todos[0].notes, "oat");
+        assert_eq!(merge.todos[0].stamps.notes.day(), 3);
+        assert!(merge.todos[0].resolved);
+        assert_eq!(merge.conflicts.len(), 1);
+        assert!(merge.conflicts[0].mine.is_none());
+        assert_eq!(merge.conflicts[0].theirs.as_ref().unwrap().notes, "rye");
+    }
+
+    #[test]
+    fn test_order_and_pins_are_merged() {
+        let base = store(&[todo("a", "milk", false, 1), todo("b", "bread", false, 1)]);
+        let mut pinned = todo("a", "milk", false, 1);
+        pinned.pinned = true;
+        pinned.stamps.pinned = Utc.with_ymd_and_hms(2023, 1, 2, 12, 0, 0).unwrap();
+        let mine = store(&[pinned]);
+        let mut moved = todo("a", "milk", false, 1);
+        moved.position = Some(512);
+        moved.stamps.position = Utc.with_ymd_and_hms(2023, 1, 3, 12, 0, 0).unwrap();
+        let mut bread = todo("b", "bread", false, 1);
+        bread.pinned = true;
+        let theirs = store(&[moved, bread]);
+
+        let merge = Merge::new(&base, &mine, &theirs);
+        assert_eq!(merge.todos.len(), 1);
+        assert!(merge.todos[0].pinned);
+        assert_eq!(merge.todos[0].position, Some(512));
+        assert_eq!(merge.todos[0].stamps.position.day(), 3);
+        assert_eq!(merge.conflicts.len(), 1);
+        assert!(merge.conflicts[0].theirs.as_ref().unwrap().pinned);
+    }
+
+    #[test]
+    fn test_first_sync_keeps_newest_fields() {
+        let mine = store(&[todo("a", "milk", true, 2)]);
+        let theirs = store(&[todo("a", "oat milk", false, 3)]);
+
+        let merge = Merge::new(&BTreeMap::new(), &mine, &theirs);
+        assert!(merge.conflicts.is_empty());
+        assert_eq!(merge.todos[0].message, "oat milk");
+        assert!(!merge.todos[0].resolved);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::todo::{Priority, Todo};
+use console::{measure_text_width, pad_str, truncate_str, Alignment};
+
+const SEPARATOR: &str = " │ ";
+const MIN_TITLE: usize = 12;
+
+pub struct Row {
+    pub resolved: bool,
+    pub line: String,
+}
+
+pub struct Table {
+    pub header: String,
+    pub rule: String,
+    pub rows: Vec<Row>,
+}
+
+impl Table {
+    pub fn layout(todos: &[(u32, Todo)], show_keys: bool, width: usize) -> Self {
+        let cells = todos
+            .iter()
+            .map(|(key, todo)| {
+                let mut cells = Vec::new();
+                if show_keys {
+                    cells.push(key.to_string());
+                }
+                cells.push(if todo.resolved { "✅" } else { "⏳" }.to_string());
+                cells.push(
+                    todo.priority()
+                        .map_or("-", |priority| match priority {
+                            Priority::High => "alta",
+                            Priority::Medium => "média",
+                            Priority::Low => "baixa",
+                        })
+                        .to_string(),
+                );
+                cells.push(
+                    todo.due()
+                        .map_or("-".to_string(), |due| due.format("%d/%m/%Y").to_string()),
+                );
+                cells.push(title(todo));
+                cells
+            })
+            .collect::<Vec<Vec<String>>>();
+
+        let mut titles = vec!["Status", "Prior.", "Prazo", "Título"];
+        if show_keys {
+            titles.insert(0, "#");
+        }
+        let mut widths = titles
+            .iter()
+            .enumerate()
+            .map(|(column, title)| {
+                cells
+                    .iter()
+                    .map(|row| measure_text_width(&row[column]))
+                    .chain([measure_text_width(title)])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect::<Vec<usize>>();
+        let last = widths.len() - 1;
+        let fixed = widths[..last].iter().sum::<usize>() + measure_text_width(SEPARATOR) * last;
+        widths[last] = widths[last].min(width.saturating_sub(fixed).max(MIN_TITLE));
+
+        let line = |row: &[String]| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| fit(cell, *width))
+                .collect::<Vec<String>>()
+                .join(SEPARATOR)
+                .trim_end()
+                .to_string()
+        };
+        Self {
+            header: line(
+                &titles
+                    .iter()
+                    .map(|title| title.to_string())
+                    .collect::<Vec<String>>(),
+            ),
+            rule: widths
+                .iter()
+                .map(|width| "─".repeat(*width))
+                .collect::<Vec<String>>()
+                .join("─┼─"),
+            rows: todos
+                .iter()
+                .zip(&cells)
+                .map(|((_, todo), row)| Row {
+                    resolved: todo.resolved,
+                    line: line(row),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn title(todo: &Todo) -> String {
+    let mut title = todo
+        .message
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ");
+    if todo.pinned {
+        title.insert_str(0, "📌 ");
+    }
+    if !todo.notes.is_empty() {
+        title.push_str(" 📝 ");
+        title.push_str(
+            &todo
+                .notes
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" "),
+        );
+    }
+    title
+}
+
+fn fit(cell: &str, width: usize) -> String {
+    match measure_text_width(cell) <= width {
+        true => pad_str(cell, width, Alignment::Left, None).to_string(),
+        false => truncate_str(cell, width, "…").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_fits_the_terminal_width() {
+        let mut pinned = Todo::new("café com o time 🎉 amanhã cedo !high".to_string());
+        pinned.pinned = true;
+        let mut resolved = Todo::new("enviar fatura due:2023-03-05".to_string());
+        resolved.resolved = true;
+        resolved.notes = "conferir valores".to_string();
+        let todos = vec![(7, pinned), (12, resolved)];
+
+        let table = Table::layout(&todos, true, 60);
+        assert_eq!(table.header, "#  │ Status │ Prior. │ Prazo      │ Título");
+        assert_eq!(
+            table.rule,
+            "───┼────────┼────────┼────────────┼─────────────────────────"
+        );
+        assert_eq!(
+            table.rows[0].line,
+            "7  │ ⏳     │ alta   │ -          │ 📌 café com o time 🎉 a…"
+        );
+        assert_eq!(
+            table.rows[1].line,
+            "12 │ ✅     │ -      │ 05/03/2023 │ enviar fatura due:2023-…"
+        );
+        assert!(table.rows[1].resolved);
+        for line in [&table.header, &table.rule, &table.rows[0].line] {
+            assert!(measure_text_width(line) <= 60);
+        }
+
+        let table = Table::layout(&todos[..1], false, 200);
+        assert_eq!(
+            table.rows[0].line,
+            "⏳     │ alta   │ -     │ 📌 café com o time 🎉 amanhã cedo !high"
+        );
+        let table = Table::layout(&todos[..1], false, 10);
+        assert_eq!(measure_text_width(&table.rows[0].line), 38);
+    }
+}
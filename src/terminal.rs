@@ -1,13 +1,42 @@
-use crate::command::UserCommand;
+use crate::backup::Backup;
+use crate::command::{Arrange, PageTurn, UserCommand};
+use crate::error::StorageError;
+use crate::git::Commit;
+use crate::index::Page;
+use crate::query::Query;
+use crate::recovery::LoadReport;
+use crate::report::Options;
+use crate::search::Search;
+use crate::smart::SmartList;
+use crate::sort::{Move, Sort};
+use crate::stats::{self, Stats};
+use crate::sync::{Conflict, Resolution};
+use crate::table::Table;
+use crate::timeline::{self, Change};
 use crate::todo::Todo;
+use crate::trash::TrashItem;
+use crate::views::View;
+use crate::watch::Reload;
+use chrono::{DateTime, Local, Utc};
 use console::{style, Style, Term};
 use std::io::Error;
 use std::num::ParseIntError;
+use std::ops::Range;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Stdin, Stdout};
 
+const TABLE_CHROME: usize = 8;
+const MIN_PAGE_SIZE: usize = 5;
+
 pub struct Terminal {
     input: BufReader<Stdin>,
     output: Stdout,
+    highlight: Option<Search>,
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Terminal {
@@ -15,7 +44,30 @@ impl Terminal {
         Self {
             input: BufReader::new(tokio::io::stdin()),
             output: tokio::io::stdout(),
+            highlight: None,
+        }
+    }
+
+    fn highlighted(&self, text: &str, base: Style) -> String {
+        let Some(search) = &self.highlight else {
+            return base.apply_to(text).to_string();
+        };
+        let mut styled = String::new();
+        let mut last = 0;
+        for range in search.find(text) {
+            styled.push_str(&base.apply_to(&text[last..range.start]).to_string());
+            styled.push_str(
+                &base
+                    .clone()
+                    .bold()
+                    .underlined()
+                    .apply_to(&text[range.clone()])
+                    .to_string(),
+            );
+            last = range.end;
         }
+        styled.push_str(&base.apply_to(&text[last..]).to_string());
+        styled
     }
 }
 
@@ -23,10 +75,11 @@ impl Terminal {
 #[async_trait::async_trait]
 pub trait UserInterface {
     async fn get_user_command(&mut self) -> Result<UserCommand, TerminalError>;
-    async fn show_options(&mut self) -> Result<(), TerminalError>;
+    async fn show_options(&mut self, views: &[View]) -> Result<(), TerminalError>;
     async fn finish_todo(&mut self) -> Result<(), TerminalError>;
     async fn show_invalid_option(&mut self) -> Result<(), TerminalError>;
     async fn ask_for_new_todo(&mut self) -> Result<Todo, TerminalError>;
+    async fn ask_for_notes(&mut self, current: &str) -> Result<String, TerminalError>;
     async fn show_todo(&mut self, todo: &Todo, msg_initial: &str) -> Result<(), TerminalError>;
     fn show_error(&self, error: TerminalError);
     async fn ask_key_todo_update(&mut self) -> Result<(), TerminalError>;
@@ -38,6 +91,38 @@ pub trait UserInterface {
     async fn write_styled(&mut self, message: &str, style: Style) -> Result<(), TerminalError>;
     fn or_not_found(&self, maybe_todo: Option<Todo>) -> Result<Todo, TerminalError>;
     async fn get_key_todo_resolve(&mut self) -> Result<(), TerminalError>;
+    async fn ask_undo_steps(&mut self) -> Result<(), TerminalError>;
+    async fn ask_redo_steps(&mut self) -> Result<(), TerminalError>;
+    async fn ask_for_date(&mut self, question: &str) -> Result<DateTime<Utc>, TerminalError>;
+    async fn show_change(&mut self, change: &Change) -> Result<(), TerminalError>;
+    async fn show_backups(&mut self, backups: &[Backup]) -> Result<(), TerminalError>;
+    async fn ask_key_backup(&mut self) -> Result<(), TerminalError>;
+    async fn confirm(&mut self, question: &str) -> Result<bool, TerminalError>;
+    async fn show_load_report(&mut self, report: &LoadReport) -> Result<(), TerminalError>;
+    async fn show_reload(&mut self, reload: &Reload) -> Result<(), TerminalError>;
+    async fn show_unsaved(&mut self) -> Result<(), TerminalError>;
+    async fn ask_passphrase(&mut self, question: &str) -> Result<String, TerminalError>;
+    async fn show_commits(&mut self, commits: &[Commit]) -> Result<(), TerminalError>;
+    async fn ask_key_commit(&mut self) -> Result<(), TerminalError>;
+    async fn ask_sync_file(&mut self) -> Result<String, TerminalError>;
+    async fn ask_conflict(&mut self, conflict: &Conflict) -> Result<Resolution, TerminalError>;
+    async fn ask_query(&mut self) -> Result<Query, TerminalError>;
+    async fn ask_sort(&mut self, current: &Sort) -> Result<Sort, TerminalError>;
+    async fn show_page_footer(&mut self, page: &Page) -> Result<(), TerminalError>;
+    async fn show_table(&mut self, page: &Page, show_keys: bool) -> Result<(), TerminalError>;
+    fn page_size(&self) -> usize;
+    async fn ask_page_turn(&mut self, page: &Page) -> Result<PageTurn, TerminalError>;
+    async fn ask_search(&mut self) -> Result<String, TerminalError>;
+    fn highlight(&mut self, search: Option<Search>);
+    async fn show_trash(&mut self, trashed: &[TrashItem]) -> Result<(), TerminalError>;
+    async fn ask_key_trash(&mut self) -> Result<(), TerminalError>;
+    async fn show_views(&mut self, views: &[View]) -> Result<(), TerminalError>;
+    async fn ask_view_name(&mut self) -> Result<String, TerminalError>;
+    async fn ask_view_query(&mut self) -> Result<String, TerminalError>;
+    async fn ask_arrange(&mut self) -> Result<Option<(u32, Arrange)>, TerminalError>;
+    async fn show_stats(&mut self, stats: &Stats) -> Result<(), TerminalError>;
+    async fn ask_report_options(&mut self) -> Result<Options, TerminalError>;
+    async fn show_report(&mut self, report: &str, path: &str) -> Result<(), TerminalError>;
 }
 
 #[async_trait::async_trait]
@@ -51,12 +136,37 @@ impl UserInterface for Terminal {
             "3" => Ok(UserCommand::Resolve),
             "4" => Ok(UserCommand::Update),
             "5" => Ok(UserCommand::Delete),
+            "6" => Ok(UserCommand::Undo),
+            "7" => Ok(UserCommand::Redo),
+            "8" => Ok(UserCommand::TimeTravel),
+            "9" => Ok(UserCommand::Compare),
+            "10" => Ok(UserCommand::Backups),
+            "11" => Ok(UserCommand::Passphrase),
+            "12" => Ok(UserCommand::GitHistory),
+            "13" => Ok(UserCommand::GitSync),
+            "14" => Ok(UserCommand::Sync),
+            "15" => Ok(UserCommand::Renumber),
+            "16" => Ok(UserCommand::Trash),
+            "17" => Ok(UserCommand::Search),
+            "18" => Ok(UserCommand::Views),
+            "19" => Ok(UserCommand::Reorder),
+            "20" => Ok(UserCommand::Stats),
+            "21" => Ok(UserCommand::Report),
+            "22" => Ok(UserCommand::Notes),
             "0" => Ok(UserCommand::Exit),
-            _ => Ok(UserCommand::Other),
+            response => {
+                if let Some(name) = response.strip_prefix('@') {
+                    return Ok(UserCommand::OpenView(name.to_string()));
+                }
+                Ok(SmartList::ALL
+                    .into_iter()
+                    .find(|list| list.shortcut() == response.to_lowercase())
+                    .map_or(UserCommand::Other, UserCommand::SmartList))
+            }
         }
     }
 
-    async fn show_options(&mut self) -> Result<(), TerminalError> {
+    async fn show_options(&mut self, views: &[View]) -> Result<(), TerminalError> {
         self.write_styled(
             "\nEscolha uma opção para usar seu TODO LIST 🤔\n",
             Style::new().magenta(),
@@ -66,14 +176,35 @@ impl UserInterface for Terminal {
             r"
     1 - Para CRIAR um TODO
     2 - Para LISTAR todos os seus TODO's
+        2a - Atrasados | 2h - Hoje | 2s - Esta semana | 2p - Próximos 7 dias | 2d - Sem data
     3 - Para RESOLVER UM TODO
     4 - Para ALTERAR um TODO existente
     5 - Para DELETAR um TODO
+    6 - Para DESFAZER operações
+    7 - Para REFAZER operações
+    8 - Para VER a lista em uma data passada
+    9 - Para COMPARAR a lista entre duas datas
+    10 - Para VER e RESTAURAR backups
+    11 - Para DEFINIR ou ALTERAR a senha do arquivo
+    12 - Para VER o histórico de versões (git)
+    13 - Para SINCRONIZAR com o remoto (git)
+    14 - Para SINCRONIZAR com outra cópia da lista
+    15 - Para RENUMERAR os TODO's (compactar as chaves)
+    16 - Para VER a LIXEIRA, restaurar ou esvaziar
+    17 - Para BUSCAR TODOs (texto, re:regex ou ~aproximada)
+    18 - Para SALVAR ou EXCLUIR visões (filtros com nome)
+    19 - Para ORDENAR os TODO's à mão ou FIXAR no topo
+    20 - Para VER as ESTATÍSTICAS da lista
+    21 - Para EXPORTAR um RELATÓRIO dos TODO's resolvidos
+    22 - Para ANOTAR um TODO (notas)
     0 - Para SAIR
     ",
             Style::new().white(),
         )
         .await?;
+        if !views.is_empty() {
+            self.show_views(views).await?;
+        }
         Ok(())
     }
 
@@ -104,11 +235,46 @@ impl UserInterface for Terminal {
         Ok(Todo::new(new_todo))
     }
 
+    async fn ask_for_notes(&mut self, current: &str) -> Result<String, TerminalError> {
+        if !current.is_empty() {
+            self.write_styled(
+                &format!("\n📝 Notas atuais: {current}\n"),
+                Style::new().white().dim(),
+            )
+            .await?;
+        }
+        self.write_styled(
+            "\nQuais notas deseja guardar? (deixe em branco para apagar) 📝\n",
+            Style::new().magenta(),
+        )
+        .await?;
+        Ok(self.input().await?.trim().to_string())
+    }
+
     async fn show_todo(&mut self, todo: &Todo, msg_initial: &str) -> Result<(), TerminalError> {
-        let todo_msg = match todo.resolved {
-            false => format!("{msg_initial}{}", &style(&*todo.message).yellow().italic()),
-            true => format!("✅  {}", &style(&*todo.message).yellow().italic().dim()),
+        let pin = if todo.pinned { "📌 " } else { "" };
+        let mut todo_msg = match todo.resolved {
+            false => format!(
+                "{msg_initial}{pin}{}",
+                self.highlighted(&todo.message, Style::new().yellow().italic())
+            ),
+            true => {
+                let message = self.highlighted(&todo.message, Style::new().yellow().italic().dim());
+                match msg_initial.trim_end_matches(": ").parse::<u32>() {
+                    Ok(key) => format!("✅  {key}: {pin}{message}"),
+                    Err(_) => format!("✅  {pin}{message}"),
+                }
+            }
         };
+        if !todo.notes.is_empty() {
+            if !todo.message.ends_with('\n') {
+                todo_msg.push('\n');
+            }
+            todo_msg.push_str(&format!(
+                "    📝 {}\n",
+                self.highlighted(&todo.notes, Style::new().white().dim())
+            ));
+        }
 
         self.output
             .write(todo_msg.as_bytes())
@@ -195,6 +361,585 @@ impl UserInterface for Terminal {
 
         Ok(())
     }
+
+    async fn ask_undo_steps(&mut self) -> Result<(), TerminalError> {
+        self.write_styled(
+            "\nQuantas operações deseja DESFAZER? ↩️\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn ask_redo_steps(&mut self) -> Result<(), TerminalError> {
+        self.write_styled(
+            "\nQuantas operações deseja REFAZER? ↪️\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn ask_for_date(&mut self, question: &str) -> Result<DateTime<Utc>, TerminalError> {
+        self.write_styled(question, Style::new().blue().bold())
+            .await?;
+        self.write_styled(
+            "(AAAA-MM-DD, AAAA-MM-DD HH:MM, hoje, ontem, sexta, 3d...)\n",
+            Style::new().white().dim(),
+        )
+        .await?;
+        let response = self.input().await?;
+        timeline::parse_moment(&response, Local::now())
+    }
+
+    async fn show_change(&mut self, change: &Change) -> Result<(), TerminalError> {
+        let change_msg = match change {
+            Change::Added { key, todo } => {
+                format!("➕ {key}: {}\n", style(todo.message.trim()).green())
+            }
+            Change::Resolved { key, todo } => {
+                format!("✅ {key}: {}\n", style(todo.message.trim()).green().dim())
+            }
+            Change::Reopened { key, todo } => {
+                format!("🔄 {key}: {}\n", style(todo.message.trim()).yellow())
+            }
+            Change::Edited { key, before, after } => format!(
+                "✏️  {key}: {} ➜ {}\n",
+                style(before.message.trim()).dim(),
+                style(after.message.trim()).yellow()
+            ),
+            Change::Deleted { key, todo } => {
+                format!("❌ {key}: {}\n", style(todo.message.trim()).red())
+            }
+            Change::Moved { from, key, todo } => {
+                format!("↕️  {from} ➜ {key}: {}\n", style(todo.message.trim()).dim())
+            }
+        };
+
+        self.output
+            .write(change_msg.as_bytes())
+            .await
+            .map_err(TerminalError::StdoutErr)?;
+        Ok(())
+    }
+
+    async fn show_backups(&mut self, backups: &[Backup]) -> Result<(), TerminalError> {
+        self.write_styled("\nBackups disponíveis: 🗄️\n\n", Style::new().blue().bold())
+            .await?;
+        for (position, backup) in backups.iter().enumerate() {
+            let backup_msg = format!(
+                "{}: {}\n",
+                position + 1,
+                style(backup.at.with_timezone(&Local).format("%d/%m/%Y %H:%M:%S")).yellow()
+            );
+            self.output
+                .write(backup_msg.as_bytes())
+                .await
+                .map_err(TerminalError::StdoutErr)?;
+        }
+        Ok(())
+    }
+
+    async fn ask_search(&mut self) -> Result<String, TerminalError> {
+        self.write_styled(
+            "\nO que deseja buscar? (texto, re:expressão ou ~aproximada) 🔎\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        Ok(self.input().await?.trim().to_string())
+    }
+
+    fn highlight(&mut self, search: Option<Search>) {
+        self.highlight = search;
+    }
+
+    async fn show_trash(&mut self, trashed: &[TrashItem]) -> Result<(), TerminalError> {
+        self.write_styled("\nTODOs na lixeira: 🗑️\n\n", Style::new().blue().bold())
+            .await?;
+        for (position, item) in trashed.iter().enumerate() {
+            let item_msg = format!(
+                "{}: {} {}\n",
+                position + 1,
+                item.todo.message.trim_end(),
+                style(format!(
+                    "(chave {}, removido em {})",
+                    item.key,
+                    item.at.with_timezone(&Local).format("%d/%m/%Y %H:%M")
+                ))
+                .dim()
+            );
+            self.output
+                .write(item_msg.as_bytes())
+                .await
+                .map_err(TerminalError::StdoutErr)?;
+        }
+        Ok(())
+    }
+
+    async fn ask_key_trash(&mut self) -> Result<(), TerminalError> {
+        self.write_styled(
+            "\nDigite o número do TODO que deseja RESTAURAR ou 0 para ESVAZIAR a lixeira: ♻️\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn show_views(&mut self, views: &[View]) -> Result<(), TerminalError> {
+        self.write_styled(
+            "\nVisões salvas (digite @nome para abrir): 👓\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        if views.is_empty() {
+            return self
+                .write_styled("    Nenhuma visão salva\n", Style::new().white().dim())
+                .await;
+        }
+        for view in views {
+            let sort = match view.sort.is_manual() {
+                true => String::new(),
+                false => format!(" (ordem: {})", view.sort),
+            };
+            let view_msg = format!(
+                "    @{} {}\n",
+                view.name,
+                style(format!("{}{sort}", view.query)).dim()
+            );
+            self.output
+                .write(view_msg.as_bytes())
+                .await
+                .map_err(TerminalError::StdoutErr)?;
+        }
+        Ok(())
+    }
+
+    async fn ask_view_name(&mut self) -> Result<String, TerminalError> {
+        self.write_styled(
+            "\nNome da visão para SALVAR ou EXCLUIR (ENTER para voltar): 👓\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        Ok(self.input().await?.trim().to_string())
+    }
+
+    async fn ask_view_query(&mut self) -> Result<String, TerminalError> {
+        self.write_styled(
+            "\nFiltro da visão (ex.: status:open due<=hoje) ou ENTER para EXCLUIR: 🔎\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        Ok(self.input().await?.trim().to_string())
+    }
+
+    async fn ask_arrange(&mut self) -> Result<Option<(u32, Arrange)>, TerminalError> {
+        self.write_styled(
+            "\nDigite o número do TODO e a ação: s (subir), d (descer), t (topo), f (fim) ou p (fixar). Ex.: 3 t — ENTER para voltar ↕️\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        let answer = self.input().await?;
+        let mut words = answer.split_whitespace();
+        let Some(key) = words.next() else {
+            return Ok(None);
+        };
+        let key = key.parse().map_err(TerminalError::ParseErr)?;
+        let arrange = match words.next().map(str::to_lowercase).as_deref() {
+            Some("s" | "subir") => Arrange::Move(Move::Up),
+            Some("d" | "descer") => Arrange::Move(Move::Down),
+            Some("t" | "topo") => Arrange::Move(Move::Top),
+            Some("f" | "fim") => Arrange::Move(Move::Bottom),
+            Some("p" | "fixar") => Arrange::Pin,
+            Some(action) => {
+                return Err(TerminalError::InvalidArgument(format!(
+                    "❗ Ação desconhecida '{action}'. Use s, d, t, f ou p ❗"
+                )))
+            }
+            None => {
+                return Err(TerminalError::InvalidArgument(
+                    "❗ Informe a ação depois do número. Ex.: 3 t ❗".to_string(),
+                ))
+            }
+        };
+        Ok(Some((key, arrange)))
+    }
+
+    async fn show_stats(&mut self, stats: &Stats) -> Result<(), TerminalError> {
+        self.write_styled(
+            "\nEstatísticas da lista: 📊\n\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        let percent = |rate: f64| format!("{:.0}%", rate * 100.0);
+        let time_to_resolve = match stats.time_to_resolve {
+            Some(waited) if waited.num_days() > 0 => {
+                format!("{}d {}h", waited.num_days(), waited.num_hours() % 24)
+            }
+            Some(waited) => format!("{}h {}min", waited.num_hours(), waited.num_minutes() % 60),
+            None => "sem dados".to_string(),
+        };
+        let completions = stats
+            .completions
+            .iter()
+            .map(|count| *count as f64)
+            .collect::<Vec<f64>>();
+        let busiest = stats.completions.iter().max().copied().unwrap_or(0);
+        let summary = format!(
+            "    Total: {}   Abertos: {}   Resolvidos: {}   Conclusão: {}\n    Tempo médio até resolver: {}\n\n    Resolvidos por dia (30 dias): {} {}\n    Conclusão por semana (8 semanas): {} {}\n",
+            style(stats.total()).bold(),
+            style(stats.open).yellow().bold(),
+            style(stats.resolved).green().bold(),
+            style(percent(stats.rate())).cyan().bold(),
+            style(time_to_resolve).cyan(),
+            style(stats::sparkline(&completions, busiest as f64)).green(),
+            style(format!("(máx. {busiest}/dia)")).dim(),
+            style(stats::sparkline(&stats.rates, 1.0)).cyan(),
+            style(percent(stats.rates.last().copied().unwrap_or(0.0))).dim(),
+        );
+        self.output
+            .write(summary.as_bytes())
+            .await
+            .map_err(TerminalError::StdoutErr)?;
+
+        if !stats.oldest.is_empty() {
+            self.write_styled("\n    Abertos há mais tempo:\n", Style::new().magenta())
+                .await?;
+            for (key, todo) in &stats.oldest {
+                let oldest_msg = format!(
+                    "    {key}: {} {}\n",
+                    todo.message.trim_end(),
+                    style(format!(
+                        "(desde {})",
+                        todo.stamps.created.with_timezone(&Local).format("%d/%m/%Y")
+                    ))
+                    .dim()
+                );
+                self.output
+                    .write(oldest_msg.as_bytes())
+                    .await
+                    .map_err(TerminalError::StdoutErr)?;
+            }
+        }
+        for (title, prefix, breakdowns) in [
+            ("Por tag", '#', &stats.tags),
+            ("Por visão", '@', &stats.lists),
+        ] {
+            if breakdowns.is_empty() {
+                continue;
+            }
+            self.write_styled(&format!("\n    {title}:\n"), Style::new().magenta())
+                .await?;
+            for breakdown in breakdowns.iter().take(10) {
+                let breakdown_msg = format!(
+                    "    {} {} abertos, {} resolvidos\n",
+                    style(format!("{prefix}{}", breakdown.name)).cyan(),
+                    style(breakdown.open).yellow(),
+                    style(breakdown.resolved).green()
+                );
+                self.output
+                    .write(breakdown_msg.as_bytes())
+                    .await
+                    .map_err(TerminalError::StdoutErr)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn ask_report_options(&mut self) -> Result<Options, TerminalError> {
+        self.write_styled(
+            "\nAgrupar por tag ou visão e formato md, txt ou html (ENTER = tag md): 📝\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        let options = self.input().await?;
+        Options::parse(&options)
+    }
+
+    async fn show_report(&mut self, report: &str, path: &str) -> Result<(), TerminalError> {
+        self.write_styled(
+            &format!("\nRelatório salvo em {path} 📝\n\n"),
+            Style::new().green().bold(),
+        )
+        .await?;
+        self.write_styled(report, Style::new().white()).await
+    }
+
+    async fn ask_key_backup(&mut self) -> Result<(), TerminalError> {
+        self.write_styled(
+            "\nDigite o número do backup que deseja VISUALIZAR: 🔍\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn confirm(&mut self, question: &str) -> Result<bool, TerminalError> {
+        self.write_styled(question, Style::new().magenta().bold())
+            .await?;
+        let response = self.input().await?;
+        Ok(matches!(
+            response.trim().to_lowercase().as_str(),
+            "s" | "sim"
+        ))
+    }
+
+    async fn ask_passphrase(&mut self, question: &str) -> Result<String, TerminalError> {
+        self.write_styled(question, Style::new().blue().bold())
+            .await?;
+        self.output
+            .flush()
+            .await
+            .map_err(TerminalError::StdoutErr)?;
+        tokio::task::spawn_blocking(|| Term::stdout().read_secure_line())
+            .await
+            .map_err(|err| TerminalError::StdinErr(err.into()))?
+            .map_err(TerminalError::StdinErr)
+    }
+
+    async fn show_commits(&mut self, commits: &[Commit]) -> Result<(), TerminalError> {
+        self.write_styled("\nVersões da lista: 🗂️\n\n", Style::new().blue().bold())
+            .await?;
+        for (position, commit) in commits.iter().enumerate() {
+            let commit_msg = format!(
+                "{}: {} {} {}\n",
+                position + 1,
+                style(&commit.id).dim(),
+                style(&commit.date).cyan(),
+                style(&commit.message).yellow()
+            );
+            self.output
+                .write(commit_msg.as_bytes())
+                .await
+                .map_err(TerminalError::StdoutErr)?;
+        }
+        Ok(())
+    }
+
+    async fn ask_key_commit(&mut self) -> Result<(), TerminalError> {
+        self.write_styled(
+            "\nDigite o número da versão que deseja RESTAURAR: ⏪\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn ask_sync_file(&mut self) -> Result<String, TerminalError> {
+        self.write_styled(
+            "\nDigite o caminho da outra cópia da lista: 📂\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        let file = self.input().await?;
+        Ok(file.trim().to_string())
+    }
+
+    async fn ask_query(&mut self) -> Result<Query, TerminalError> {
+        self.write_styled(
+            "\nFiltrar por (ENTER = todos). Ex.: status:open tag:casa due<7d priority>=high -tag:algum \"texto\" 🔎\n",
+            Style::new().blue().bold(),
+        )
+        .await?;
+        let filter = self.input().await?;
+        Query::parse(filter.trim())
+    }
+
+    async fn ask_sort(&mut self, current: &Sort) -> Result<Sort, TerminalError> {
+        self.write_styled(
+            &format!("\nOrdenar por (ENTER = {current}): manual, criado, prazo, prioridade, alfabetica ou status. Até duas, com asc, desc ou inverter ↕️\n"),
+            Style::new().blue().bold(),
+        )
+        .await?;
+        let sort = self.input().await?;
+        Sort::parse(&sort, *current)
+    }
+
+    async fn show_page_footer(&mut self, page: &Page) -> Result<(), TerminalError> {
+        self.write_styled(
+            &format!(
+                "\n    {}–{} de {} TODO(s)\n",
+                page.first(),
+                page.last(),
+                page.total
+            ),
+            Style::new().white().dim(),
+        )
+        .await
+    }
+
+    async fn show_table(&mut self, page: &Page, show_keys: bool) -> Result<(), TerminalError> {
+        if page.todos.is_empty() {
+            return self
+                .write_styled("    Nenhum TODO encontrado\n", Style::new().white().dim())
+                .await;
+        }
+        let (_, columns) = Term::stdout().size();
+        let table = Table::layout(&page.todos, show_keys, usize::from(columns));
+        let mut table_msg = format!(
+            "{}\n{}\n",
+            style(&table.header).blue().bold(),
+            style(&table.rule).dim()
+        );
+        for row in &table.rows {
+            let line = match row.resolved {
+                true => style(&row.line).dim(),
+                false => style(&row.line).yellow(),
+            };
+            table_msg.push_str(&format!("{line}\n"));
+        }
+        self.output
+            .write(table_msg.as_bytes())
+            .await
+            .map_err(TerminalError::StdoutErr)?;
+        Ok(())
+    }
+
+    fn page_size(&self) -> usize {
+        let (rows, _) = Term::stdout().size();
+        usize::from(rows)
+            .saturating_sub(TABLE_CHROME)
+            .max(MIN_PAGE_SIZE)
+    }
+
+    async fn ask_page_turn(&mut self, page: &Page) -> Result<PageTurn, TerminalError> {
+        let mut choices = Vec::new();
+        if page.has_next() {
+            choices.push("n (próxima)");
+        }
+        if page.number > 0 {
+            choices.push("p (anterior)");
+        }
+        self.write_styled(
+            &format!("\n{} ou ENTER para sair 📖\n", choices.join(", ")),
+            Style::new().blue().bold(),
+        )
+        .await?;
+        let answer = self.input().await?;
+        Ok(match answer.trim().to_lowercase().as_str() {
+            "n" | "próxima" | "proxima" | "s" => PageTurn::Next,
+            "p" | "anterior" => PageTurn::Previous,
+            _ => PageTurn::Stop,
+        })
+    }
+
+    async fn ask_conflict(&mut self, conflict: &Conflict) -> Result<Resolution, TerminalError> {
+        self.write_styled(
+            "\n⚔️  O mesmo TODO foi alterado nas duas cópias:\n\n",
+            Style::new().yellow().bold(),
+        )
+        .await?;
+        for (label, todo) in [("Minha", &conflict.mine), ("Outra", &conflict.theirs)] {
+            let todo_msg = match todo {
+                Some(todo) if todo.resolved => format!("✅ {}", todo.message.trim()),
+                Some(todo) => format!("⏳ {}", todo.message.trim()),
+                None => "❌ (excluído)".to_string(),
+            };
+            self.output
+                .write(format!("{}: {todo_msg}\n", style(label).cyan()).as_bytes())
+                .await
+                .map_err(TerminalError::StdoutErr)?;
+        }
+
+        loop {
+            self.write_styled(
+                "\n1 - Manter a MINHA  2 - Manter a OUTRA  3 - Manter AMBAS\n",
+                Style::new().magenta().bold(),
+            )
+            .await?;
+            match self.input().await?.trim() {
+                "1" => return Ok(Resolution::Mine),
+                "2" => return Ok(Resolution::Theirs),
+                "3" => return Ok(Resolution::Both),
+                _ => self.show_error(TerminalError::InvalidArgument(
+                    "❗ Opção inválida ❗".to_string(),
+                )),
+            }
+        }
+    }
+
+    async fn show_load_report(&mut self, report: &LoadReport) -> Result<(), TerminalError> {
+        if report.created {
+            self.write_styled(
+                "✨ Primeira execução: arquivo de TODOs criado! ✨\n",
+                Style::new().green(),
+            )
+            .await?;
+        }
+
+        if let Some(quarantine) = &report.quarantine {
+            self.write_styled(
+                &format!(
+                    "\n⚠️  {} linha(s) corrompida(s) ignorada(s) e movida(s) para {quarantine}:\n",
+                    report.rejected.len()
+                ),
+                Style::new().yellow().bold(),
+            )
+            .await?;
+            for rejected in &report.rejected {
+                self.write_styled(
+                    &format!(
+                        "    linha {} de {}: {}\n",
+                        rejected.line, rejected.file, rejected.reason
+                    ),
+                    Style::new().yellow(),
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn show_unsaved(&mut self) -> Result<(), TerminalError> {
+        self.write_styled(
+            "\n💾 Alterações não salvas...\n",
+            Style::new().yellow().dim(),
+        )
+        .await
+    }
+
+    async fn show_reload(&mut self, reload: &Reload) -> Result<(), TerminalError> {
+        self.write_styled(
+            "\n🔄 O arquivo de TODOs foi alterado externamente e a lista foi recarregada! 🔄\n",
+            Style::new().cyan().bold(),
+        )
+        .await?;
+        if reload.merged > 0 {
+            self.write_styled(
+                &format!(
+                    "    {} alteração(ões) local(is) mesclada(s) com o arquivo\n",
+                    reload.merged
+                ),
+                Style::new().cyan(),
+            )
+            .await?;
+        }
+        for (old, new) in &reload.rekeyed {
+            self.write_styled(
+                &format!("    O TODO local {old} agora é o TODO {new}\n"),
+                Style::new().yellow(),
+            )
+            .await?;
+        }
+        for flagged in &reload.flagged {
+            self.write_styled(
+                &format!(
+                    "⚠️  Alteração local não aplicada (TODO removido externamente): {flagged}\n"
+                ),
+                Style::new().yellow().bold(),
+            )
+            .await?;
+        }
+        for conflict in &reload.conflicts {
+            self.write_styled(
+                &format!(
+                    "⚠️  Alteração local não aplicada (TODO alterado externamente): {conflict}\n"
+                ),
+                Style::new().yellow().bold(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -203,15 +948,72 @@ pub enum TerminalError {
     StdinErr(Error),
     ParseErr(ParseIntError),
     NotFound(String),
+    InvalidDate(String),
+    InvalidArgument(String),
+    InvalidQuery {
+        query: String,
+        span: Range<usize>,
+        reason: String,
+    },
+    UnknownField {
+        query: String,
+        span: Range<usize>,
+        field: String,
+    },
+    Storage(StorageError),
+}
+
+impl From<StorageError> for TerminalError {
+    fn from(error: StorageError) -> Self {
+        Self::Storage(error)
+    }
 }
 
 impl TerminalError {
-    fn message_err(self) -> String {
+    pub fn message_err(self) -> String {
         match self {
             Self::StdoutErr(err) => format!("Houve um erro ao tentar exibir mensagem {}", err),
             Self::StdinErr(err) => format!("Houve um erro na entrada de dados {}", err),
             Self::ParseErr(_err) => "O valor inserido precisa ser um número".to_string(),
             Self::NotFound(err) => err,
+            Self::InvalidDate(err) => err,
+            Self::InvalidArgument(err) => err,
+            Self::InvalidQuery {
+                query,
+                span,
+                reason,
+            } => point_at(&query, span, format!("❗ Filtro inválido: {reason} ❗")),
+            Self::UnknownField { query, span, field } => point_at(
+                &query,
+                span,
+                format!(
+                    "❗ Campo desconhecido '{field}' (use status, tag, due, priority ou texto) ❗"
+                ),
+            ),
+            Self::Storage(err) => format!("❗ {err} ❗"),
         }
     }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::StdoutErr(_) | Self::StdinErr(_) => 1,
+            Self::ParseErr(_)
+            | Self::InvalidDate(_)
+            | Self::InvalidArgument(_)
+            | Self::InvalidQuery { .. }
+            | Self::UnknownField { .. } => 2,
+            Self::NotFound(_) => 3,
+            Self::Storage(_) => 4,
+        }
+    }
+}
+
+fn point_at(query: &str, span: Range<usize>, message: String) -> String {
+    let before = query[..span.start].chars().count();
+    let width = query[span].chars().count().max(1);
+    format!(
+        "{message}\n    {query}\n    {}{}",
+        " ".repeat(before),
+        "^".repeat(width)
+    )
 }
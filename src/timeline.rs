@@ -0,0 +1,224 @@
+use crate::terminal::TerminalError;
+use crate::todo::Todo;
+use chrono::{
+    DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Utc, Weekday,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+pub enum Change {
+    Added { key: u32, todo: Todo },
+    Resolved { key: u32, todo: Todo },
+    Reopened { key: u32, todo: Todo },
+    Edited { key: u32, before: Todo, after: Todo },
+    Deleted { key: u32, todo: Todo },
+    Moved { from: u32, key: u32, todo: Todo },
+}
+
+impl Change {
+    fn key(&self) -> u32 {
+        match self {
+            Self::Added { key, .. }
+            | Self::Resolved { key, .. }
+            | Self::Reopened { key, .. }
+            | Self::Edited { key, .. }
+            | Self::Deleted { key, .. }
+            | Self::Moved { key, .. } => *key,
+        }
+    }
+}
+
+pub fn diff(before: &BTreeMap<u32, Todo>, after: &BTreeMap<u32, Todo>) -> Vec<Change> {
+    let previous = before
+        .iter()
+        .map(|(key, todo)| (todo.id.as_str(), (*key, todo)))
+        .collect::<HashMap<&str, (u32, &Todo)>>();
+    let current = after
+        .values()
+        .map(|todo| todo.id.as_str())
+        .collect::<HashSet<&str>>();
+
+    let mut changes = before
+        .iter()
+        .filter(|(_, todo)| !current.contains(todo.id.as_str()))
+        .map(|(key, todo)| Change::Deleted {
+            key: *key,
+            todo: todo.clone(),
+        })
+        .collect::<Vec<Change>>();
+    for (key, new) in after {
+        let key = *key;
+        let Some((from, old)) = previous.get(new.id.as_str()).copied() else {
+            changes.push(Change::Added {
+                key,
+                todo: new.clone(),
+            });
+            continue;
+        };
+        if from != key {
+            changes.push(Change::Moved {
+                from,
+                key,
+                todo: new.clone(),
+            });
+        }
+        if old.message != new.message {
+            changes.push(Change::Edited {
+                key,
+                before: old.clone(),
+                after: new.clone(),
+            });
+        }
+        match (old.resolved, new.resolved) {
+            (false, true) => changes.push(Change::Resolved {
+                key,
+                todo: new.clone(),
+            }),
+            (true, false) => changes.push(Change::Reopened {
+                key,
+                todo: new.clone(),
+            }),
+            _ => {}
+        }
+    }
+    changes.sort_by_key(Change::key);
+    changes
+}
+
+pub fn parse_moment(input: &str, now: DateTime<Local>) -> Result<DateTime<Utc>, TerminalError> {
+    let input = input.trim().to_lowercase();
+    let today = now.date_naive();
+    let invalid = || {
+        TerminalError::InvalidDate(format!(
+            "❗ Data inválida: '{input}'. Use AAAA-MM-DD, AAAA-MM-DD HH:MM, hoje, ontem, um dia da semana ou 3d ❗"
+        ))
+    };
+
+    let day = match input.as_str() {
+        "" | "agora" => return Ok(now.with_timezone(&Utc)),
+        "hoje" => today,
+        "ontem" => today - Duration::days(1),
+        _ => {
+            if let Ok(moment) = NaiveDateTime::parse_from_str(&input, "%Y-%m-%d %H:%M") {
+                return local_to_utc(moment).ok_or_else(invalid);
+            }
+            if let Ok(day) = NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+                day
+            } else if let Some(weekday) = parse_weekday(&input) {
+                let mut day = today - Duration::days(1);
+                while day.weekday() != weekday {
+                    day -= Duration::days(1);
+                }
+                day
+            } else {
+                input
+                    .strip_suffix('d')
+                    .and_then(|days| days.parse().ok())
+                    .and_then(Duration::try_days)
+                    .and_then(|days| today.checked_sub_signed(days))
+                    .ok_or_else(invalid)?
+            }
+        }
+    };
+
+    local_to_utc(day.and_time(NaiveTime::from_hms_opt(23, 59, 59).unwrap_or_default()))
+        .ok_or_else(invalid)
+}
+
+fn local_to_utc(moment: NaiveDateTime) -> Option<DateTime<Utc>> {
+    match Local.from_local_datetime(&moment) {
+        LocalResult::Single(moment) | LocalResult::Ambiguous(moment, _) => {
+            Some(moment.with_timezone(&Utc))
+        }
+        LocalResult::None => None,
+    }
+}
+
+pub fn parse_weekday(input: &str) -> Option<Weekday> {
+    match input.trim_end_matches("-feira") {
+        "segunda" => Some(Weekday::Mon),
+        "terça" | "terca" => Some(Weekday::Tue),
+        "quarta" => Some(Weekday::Wed),
+        "quinta" => Some(Weekday::Thu),
+        "sexta" => Some(Weekday::Fri),
+        "sábado" | "sabado" => Some(Weekday::Sat),
+        "domingo" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2023, 1, 18, 10, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_moment() {
+        let friday = parse_moment("sexta", now()).unwrap().with_timezone(&Local);
+        assert_eq!(
+            friday.date_naive(),
+            NaiveDate::from_ymd_opt(2023, 1, 13).unwrap()
+        );
+
+        let day = parse_moment("2023-01-02", now())
+            .unwrap()
+            .with_timezone(&Local);
+        assert_eq!(
+            day.date_naive(),
+            NaiveDate::from_ymd_opt(2023, 1, 2).unwrap()
+        );
+
+        let days_ago = parse_moment("3d", now()).unwrap().with_timezone(&Local);
+        assert_eq!(
+            days_ago.date_naive(),
+            NaiveDate::from_ymd_opt(2023, 1, 15).unwrap()
+        );
+
+        assert!(parse_moment("amanhã", now()).is_err());
+        assert!(parse_moment("999999999d", now()).is_err());
+        assert!(parse_moment("-999999999d", now()).is_err());
+    }
+
+    #[test]
+    fn test_diff_between_collections() {
+        let mut before = BTreeMap::new();
+        before.insert(1, Todo::new("foo".to_string()));
+        before.insert(2, Todo::new("boo".to_string()));
+
+        let mut after = before.clone();
+        after.remove(&1);
+        after.get_mut(&2).unwrap().resolved = true;
+        after.insert(3, Todo::new("Lorem".to_string()));
+
+        let changes = diff(&before, &after);
+        assert_eq!(changes.len(), 3);
+        assert!(matches!(changes[0], Change::Deleted { key: 1, .. }));
+        assert!(matches!(changes[1], Change::Resolved { key: 2, .. }));
+        assert!(matches!(changes[2], Change::Added { key: 3, .. }));
+
+        let mut renumbered = BTreeMap::new();
+        renumbered.insert(1, after[&2].clone());
+        renumbered.insert(2, after[&3].clone());
+        let changes = diff(&after, &renumbered);
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(
+            changes[0],
+            Change::Moved {
+                from: 2,
+                key: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            changes[1],
+            Change::Moved {
+                from: 3,
+                key: 2,
+                ..
+            }
+        ));
+    }
+}
@@ -1,18 +1,132 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result};
+use uuid::Uuid;
 
-#[derive(Clone)]
+pub const POSITION_GAP: i64 = 1024;
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stamps {
+    pub created: DateTime<Utc>,
+    pub message: DateTime<Utc>,
+    #[serde(default)]
+    pub notes: DateTime<Utc>,
+    pub resolved: DateTime<Utc>,
+    #[serde(default)]
+    pub position: DateTime<Utc>,
+    #[serde(default)]
+    pub pinned: DateTime<Utc>,
+}
+
+impl Stamps {
+    pub fn at(at: DateTime<Utc>) -> Self {
+        Self {
+            created: at,
+            message: at,
+            notes: at,
+            resolved: at,
+            position: at,
+            pinned: at,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl Priority {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "low" | "baixa" => Some(Self::Low),
+            "medium" | "media" | "média" => Some(Self::Medium),
+            "high" | "alta" => Some(Self::High),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Todo {
     pub message: String,
     pub resolved: bool,
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub stamps: Stamps,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<i64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
 }
 
 impl Todo {
     pub fn new(todo: String) -> Self {
         Self {
-            message: todo,
+            message: todo.replace('\t', "    "),
             resolved: false,
+            id: Uuid::new_v4().simple().to_string(),
+            stamps: Stamps::at(Utc::now()),
+            notes: String::new(),
+            position: None,
+            pinned: false,
+        }
+    }
+
+    pub fn legacy(key: u32, message: String, resolved: bool) -> Self {
+        Self {
+            message,
+            resolved,
+            id: Self::legacy_id(key),
+            stamps: Stamps::default(),
+            notes: String::new(),
+            position: None,
+            pinned: false,
         }
     }
+
+    pub fn legacy_id(key: u32) -> String {
+        format!("legacy-{key}")
+    }
+
+    pub fn place(&self, key: u32) -> i64 {
+        self.position.unwrap_or(i64::from(key) * POSITION_GAP)
+    }
+
+    pub fn tags(&self) -> Vec<String> {
+        self.message
+            .split_whitespace()
+            .filter_map(|word| word.strip_prefix('#'))
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    pub fn due(&self) -> Option<NaiveDate> {
+        self.message
+            .split_whitespace()
+            .find_map(|word| NaiveDate::parse_from_str(word.strip_prefix("due:")?, "%Y-%m-%d").ok())
+    }
+
+    pub fn scheduled(&self) -> Option<NaiveDate> {
+        self.message.split_whitespace().find_map(|word| {
+            let day = word
+                .strip_prefix("scheduled:")
+                .or_else(|| word.strip_prefix("agendado:"))?;
+            NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()
+        })
+    }
+
+    pub fn priority(&self) -> Option<Priority> {
+        self.message
+            .split_whitespace()
+            .find_map(|word| Priority::parse(word.strip_prefix('!')?))
+    }
 }
 
 impl Display for Todo {
@@ -23,12 +137,17 @@ impl Display for Todo {
 
 #[cfg(test)]
 pub mod mocks {
-    use super::Todo;
+    use super::{Stamps, Todo};
 
     factori::factori!(Todo, {
         default {
             message = "foo".to_string(),
-            resolved = false
+            resolved = false,
+            id = "foo".to_string(),
+            stamps = Stamps::default(),
+            notes = String::new(),
+            position = None,
+            pinned = false,
         }
     });
 }
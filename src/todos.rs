@@ -1,134 +1,1421 @@
-use crate::terminal::TerminalError;
-use crate::todo::Todo;
-use std::collections::BTreeMap;
-use tokio::fs::{read_to_string, write};
+use crate::backup::{Backup, BackupPolicy, BackupSchedule, Backups};
+use crate::crypto::{Codec, CryptoStorage, Vault};
+use crate::git::{Commit, GitRepo, GitStorage, Upstream, Versioning};
+use crate::history::{History, Operation};
+use crate::index::{Index, Page};
+use crate::journal::{Entry, Event, Journal, Outbox};
+use crate::query::Query;
+use crate::recovery::{LoadReport, Quarantine, RejectedLine};
+use crate::report::{Grouping, Report};
+use crate::search::{Hit, Search};
+use crate::smart::Calendar;
+use crate::sort::{Move, Sort};
+use crate::stats::Stats;
+use crate::sync::{Merge, SyncStorage};
+use crate::error::StorageError;
+use crate::todo::{Stamps, Todo, POSITION_GAP};
+use crate::trash::{Trash, TrashItem};
+use crate::views::{View, ViewStorage, Views};
+use crate::watch::{Reload, Watcher};
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::fs::{create_dir_all, metadata, remove_file, rename, write};
+use tokio::sync::Mutex;
+
+const COMPACT_AFTER: usize = 200;
+const TRASH_DAYS: i64 = 30;
 
 pub struct Todos {
     todo_collection: BTreeMap<u32, Todo>,
+    index: Index,
     length: u32,
+    history: History,
+    trash: Trash,
+    retention: Duration,
+    calendar: Calendar,
+    views: Views,
+    outbox: Outbox,
+    backups: Option<BackupSchedule>,
+    lenient: bool,
+    codec: Codec,
+    git: Versioning,
+    watcher: Watcher,
+}
+
+impl Default for Todos {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Todos {
     pub fn new() -> Self {
         Self {
             todo_collection: BTreeMap::<u32, Todo>::new(),
+            index: Index::default(),
             length: 0,
+            history: History::default(),
+            trash: Trash::default(),
+            retention: Duration::days(TRASH_DAYS),
+            calendar: Calendar::default(),
+            views: Views::default(),
+            outbox: Outbox::default(),
+            backups: None,
+            lenient: false,
+            codec: Codec::Plain,
+            git: Versioning::default(),
+            watcher: Watcher::default(),
+        }
+    }
+
+    pub fn with_backups(mut self, policy: BackupPolicy) -> Self {
+        self.backups = Some(BackupSchedule::new(policy));
+        self
+    }
+
+    pub fn with_lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    pub fn with_git(mut self, git: bool) -> Self {
+        self.git = Versioning::new(git);
+        self
+    }
+
+    pub fn with_trash_retention(mut self, days: u32) -> Self {
+        self.retention = Duration::days(days.into());
+        self
+    }
+
+    pub fn with_calendar(mut self, calendar: Calendar) -> Self {
+        self.calendar = calendar;
+        self
+    }
+
+    fn apply(&mut self, entry: &Entry) {
+        match &entry.event {
+            Event::TodoCreated { key, todo } => {
+                let mut todo = todo.clone();
+                if todo.id.is_empty() {
+                    todo.id = Todo::legacy_id(*key);
+                }
+                if todo.stamps == Stamps::default() {
+                    todo.stamps = Stamps::at(entry.at);
+                }
+                self.trash.take(&todo.id);
+                self.put(*key, todo);
+                self.length = self.length.max(*key);
+            }
+            Event::MessageChanged { key, message } => {
+                if let Some(mut todo) = self.take(*key) {
+                    todo.message = message.clone();
+                    todo.stamps.message = entry.at;
+                    self.put(*key, todo);
+                }
+            }
+            Event::NotesChanged { key, notes } => {
+                if let Some(todo) = self.todo_collection.get_mut(key) {
+                    todo.notes = notes.clone();
+                    todo.stamps.notes = entry.at;
+                }
+            }
+            Event::Resolved { key } | Event::Reopened { key } => {
+                if let Some(mut todo) = self.take(*key) {
+                    todo.resolved = matches!(entry.event, Event::Resolved { .. });
+                    todo.stamps.resolved = entry.at;
+                    self.put(*key, todo);
+                }
+            }
+            Event::Removed { key } => {
+                self.take(*key);
+            }
+            Event::Renumbered { moves } => {
+                let moved = moves
+                    .iter()
+                    .filter_map(|(old, new)| {
+                        let mut todo = self.take(*old)?;
+                        todo.position = Some(todo.place(*old));
+                        Some((*new, todo))
+                    })
+                    .collect::<Vec<(u32, Todo)>>();
+                for (key, todo) in moved {
+                    self.put(key, todo);
+                }
+                self.length = self.todo_collection.keys().last().cloned().unwrap_or(0);
+            }
+            Event::Trashed { key } => {
+                if let Some(todo) = self.take(*key) {
+                    self.trash.put(*key, todo, entry.at);
+                }
+            }
+            Event::Purged { ids } => {
+                for id in ids {
+                    self.trash.take(id);
+                }
+            }
+            Event::Moved { key, position } => {
+                if let Some(mut todo) = self.take(*key) {
+                    todo.position = Some(*position);
+                    todo.stamps.position = entry.at;
+                    self.put(*key, todo);
+                }
+            }
+            Event::Pinned { key, pinned } => {
+                if let Some(mut todo) = self.take(*key) {
+                    todo.pinned = *pinned;
+                    todo.stamps.pinned = entry.at;
+                    self.put(*key, todo);
+                }
+            }
+        }
+    }
+
+    fn put(&mut self, key: u32, todo: Todo) {
+        self.take(key);
+        self.index.insert(key, &todo);
+        self.todo_collection.insert(key, todo);
+    }
+
+    fn take(&mut self, key: u32) -> Option<Todo> {
+        let todo = self.todo_collection.remove(&key)?;
+        self.index.remove(key, &todo);
+        Some(todo)
+    }
+
+    fn replace_collection(&mut self, todos: BTreeMap<u32, Todo>) {
+        self.index = Index::build(&todos);
+        self.todo_collection = todos;
+    }
+
+    fn respace(&mut self, pinned: bool) -> Vec<Operation> {
+        let places = self.index.places(pinned);
+        let mut operations = Vec::new();
+        for (at, (place, key)) in places.into_iter().enumerate() {
+            let position = (at as i64 + 1) * POSITION_GAP;
+            let Some(before) = self.todo_collection.get(&key).cloned() else {
+                continue;
+            };
+            if place != position {
+                let mut after = before.clone();
+                after.position = Some(position);
+                operations.push(Operation::Update { key, before, after });
+                self.emit(Event::Moved { key, position });
+            }
+        }
+        operations
+    }
+
+    fn bounds(&self, key: u32, pinned: bool, to: Move) -> Option<(Option<i64>, Option<i64>)> {
+        let places = self.index.places(pinned);
+        let at = places.iter().position(|(_, other)| *other == key)?;
+        let place = |at: usize| places.get(at).map(|(place, _)| *place);
+        match to {
+            Move::Up if at > 0 => Some((at.checked_sub(2).and_then(place), place(at - 1))),
+            Move::Down if at + 1 < places.len() => Some((place(at + 1), place(at + 2))),
+            Move::Top if at > 0 => Some((None, place(0))),
+            Move::Bottom if at + 1 < places.len() => Some((place(places.len() - 1), None)),
+            _ => None,
+        }
+    }
+
+    fn page_of(
+        &self,
+        keys: impl Iterator<Item = u32>,
+        total: usize,
+        number: usize,
+        size: usize,
+    ) -> Page {
+        let todos = keys
+            .skip(number * size)
+            .take(size)
+            .filter_map(|key| Some((key, self.todo_collection.get(&key)?.clone())))
+            .collect();
+        Page {
+            todos,
+            number,
+            size,
+            total,
+        }
+    }
+
+    fn emit(&mut self, event: Event) {
+        if self.git.is_enabled() {
+            let change = self.describe(&event);
+            self.git.record(change);
+        }
+        let target = match &event {
+            Event::TodoCreated { todo, .. } => (todo.id.clone(), Stamps::default()),
+            Event::MessageChanged { key, .. }
+            | Event::NotesChanged { key, .. }
+            | Event::Resolved { key }
+            | Event::Reopened { key }
+            | Event::Removed { key }
+            | Event::Trashed { key }
+            | Event::Moved { key, .. }
+            | Event::Pinned { key, .. } => self
+                .todo_collection
+                .get(key)
+                .map(|todo| (todo.id.clone(), todo.stamps))
+                .unwrap_or_default(),
+            Event::Renumbered { .. } | Event::Purged { .. } => Default::default(),
+        };
+        let entry = Entry::now(event);
+        self.apply(&entry);
+        self.outbox.push(entry, target);
+    }
+
+    fn describe(&self, event: &Event) -> String {
+        let message = |key: &u32| {
+            self.todo_collection
+                .get(key)
+                .map(|todo| todo.message.trim().to_string())
+                .unwrap_or_default()
+        };
+        match event {
+            Event::TodoCreated { key, todo } => format!("add #{key}: {}", todo.message.trim()),
+            Event::MessageChanged { key, message } => format!("edit #{key}: {}", message.trim()),
+            Event::NotesChanged { key, .. } => format!("edit notes #{key}: {}", message(key)),
+            Event::Resolved { key } => format!("resolve #{key}: {}", message(key)),
+            Event::Reopened { key } => format!("reopen #{key}: {}", message(key)),
+            Event::Removed { key } => format!("remove #{key}: {}", message(key)),
+            Event::Renumbered { moves } => format!("renumber {} todos", moves.len()),
+            Event::Trashed { key } => format!("trash #{key}: {}", message(key)),
+            Event::Purged { ids } => format!("purge {} todos from trash", ids.len()),
+            Event::Moved { key, .. } => format!("move #{key}: {}", message(key)),
+            Event::Pinned { key, pinned: true } => format!("pin #{key}: {}", message(key)),
+            Event::Pinned { key, pinned: false } => format!("unpin #{key}: {}", message(key)),
+        }
+    }
+
+    fn set(&mut self, key: u32, target: Option<Todo>) {
+        match (self.todo_collection.get(&key).cloned(), target) {
+            (None, Some(todo)) => self.emit(Event::TodoCreated { key, todo }),
+            (Some(_), None) => self.emit(Event::Removed { key }),
+            (Some(current), Some(todo)) if current.id != todo.id => {
+                self.emit(Event::Removed { key });
+                self.emit(Event::TodoCreated { key, todo });
+            }
+            (Some(current), Some(todo)) => {
+                let position = todo.place(key);
+                if current.message != todo.message {
+                    self.emit(Event::MessageChanged {
+                        key,
+                        message: todo.message,
+                    });
+                }
+                if current.notes != todo.notes {
+                    self.emit(Event::NotesChanged {
+                        key,
+                        notes: todo.notes,
+                    });
+                }
+                match (current.resolved, todo.resolved) {
+                    (false, true) => self.emit(Event::Resolved { key }),
+                    (true, false) => self.emit(Event::Reopened { key }),
+                    _ => {}
+                }
+                if current.place(key) != position {
+                    self.emit(Event::Moved { key, position });
+                }
+                if current.pinned != todo.pinned {
+                    self.emit(Event::Pinned {
+                        key,
+                        pinned: todo.pinned,
+                    });
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn locate(&self, key: u32, id: &str) -> Option<u32> {
+        match self.todo_collection.get(&key) {
+            Some(todo) if todo.id == id => Some(key),
+            _ => self.index.key_of(id),
+        }
+    }
+
+    fn reinsert(&mut self, key: u32, todo: &Todo) {
+        let key = match self.todo_collection.get(&key) {
+            Some(current) if current.id != todo.id => self.length + 1,
+            _ => key,
+        };
+        self.set(key, Some(todo.clone()));
+    }
+
+    fn revert(&mut self, operation: &Operation) {
+        match operation {
+            Operation::Insert { key, todo } => {
+                if let Some(key) = self.locate(*key, &todo.id) {
+                    self.set(key, None);
+                }
+            }
+            Operation::Update { key, before, .. } | Operation::Resolve { key, before } => {
+                match self.locate(*key, &before.id) {
+                    Some(key) => self.set(key, Some(before.clone())),
+                    None => self.reinsert(*key, before),
+                }
+            }
+            Operation::Remove { key, todo } => self.reinsert(*key, todo),
+            Operation::Restore { key, todo } => {
+                if let Some(key) = self.locate(*key, &todo.id) {
+                    self.emit(Event::Trashed { key });
+                }
+            }
+            Operation::Group { operations } => {
+                for operation in operations.iter().rev() {
+                    self.revert(operation);
+                }
+            }
+        }
+    }
+
+    fn replay(&mut self, operation: &Operation) {
+        match operation {
+            Operation::Insert { key, todo } | Operation::Restore { key, todo } => {
+                self.reinsert(*key, todo)
+            }
+            Operation::Update { key, after, .. } => {
+                if let Some(key) = self.locate(*key, &after.id) {
+                    self.set(key, Some(after.clone()));
+                }
+            }
+            Operation::Resolve { key, before } => {
+                if let Some(key) = self.locate(*key, &before.id) {
+                    self.emit(Event::Resolved { key });
+                }
+            }
+            Operation::Remove { key, todo } => {
+                if let Some(key) = self.locate(*key, &todo.id) {
+                    self.emit(Event::Trashed { key });
+                }
+            }
+            Operation::Group { operations } => {
+                for operation in operations {
+                    self.replay(operation);
+                }
+            }
+        }
+    }
+
+    fn parse_snapshot(path: &str, snapshot: &str) -> Result<BTreeMap<u32, Todo>, StorageError> {
+        let mut todos = BTreeMap::new();
+        for (index, line) in snapshot.lines().enumerate() {
+            Self::read_snapshot_line(path, index + 1, line, &mut todos, None)?;
+        }
+        Ok(todos)
+    }
+
+    fn read_snapshot_line(
+        path: &str,
+        number: usize,
+        line: &str,
+        todos: &mut BTreeMap<u32, Todo>,
+        rejected: Option<&mut Vec<RejectedLine>>,
+    ) -> Result<(), StorageError> {
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        match (Self::parse_snapshot_line(line), rejected) {
+            (Ok((key, todo)), _) => {
+                todos.entry(key).or_insert(todo);
+            }
+            (Err(error), Some(rejected)) => rejected.push(RejectedLine {
+                file: path.to_string(),
+                line: number,
+                content: line.to_string(),
+                reason: error.cause.to_string(),
+            }),
+            (Err(error), None) => return Err(error.line(number).at(path)),
+        }
+        Ok(())
+    }
+
+    fn parse_snapshot_line(line: &str) -> Result<(u32, Todo), StorageError> {
+        let (line, meta) = match line.split_once("\t{") {
+            Some((line, meta)) => (line, Some(format!("{{{meta}"))),
+            None => (line, None),
+        };
+        let (key, todo_message, resolve) = Self::parse_line_for_todo(line)?;
+        let message = format!("{todo_message}\n");
+        let Some(meta) = meta else {
+            return Ok((key, Todo::legacy(key, message, resolve)));
+        };
+
+        let mut todo: Value = serde_json::from_str(&meta)
+            .map_err(|err| StorageError::parse(format!("metadados inválidos ({err})")))?;
+        todo["message"] = Value::String(message);
+        todo["resolved"] = Value::Bool(resolve);
+        let todo = serde_json::from_value(todo)
+            .map_err(|err| StorageError::parse(format!("metadados inválidos ({err})")))?;
+        Ok((key, todo))
+    }
+
+    fn parse_line_for_todo(line: &str) -> Result<(u32, String, bool), StorageError> {
+        let mut text_slice = line.split('-');
+        let key = text_slice.next().unwrap_or_default();
+        let key: u32 = key
+            .parse()
+            .map_err(|_| StorageError::parse(format!("chave inválida '{key}'")))?;
+
+        let resolve = match text_slice
+            .next()
+            .ok_or_else(|| StorageError::parse("status ausente"))?
+        {
+            "true" => true,
+            "false" => false,
+            status => return Err(StorageError::parse(format!("status inválido '{status}'"))),
+        };
+
+        let message = text_slice.collect::<Vec<&str>>().join("-");
+
+        Ok((key, message, resolve))
+    }
+
+    fn snapshot(&self) -> String {
+        Self::snapshot_of(&self.todo_collection)
+    }
+
+    fn snapshot_of(todos: &BTreeMap<u32, Todo>) -> String {
+        todos
+            .iter()
+            .map(|(key, todo)| {
+                let mut meta = serde_json::to_value(todo).unwrap_or_default();
+                if let Value::Object(fields) = &mut meta {
+                    fields.remove("message");
+                    fields.remove("resolved");
+                }
+                format!(
+                    "{key}-{}-{}\t{meta}\n",
+                    todo.resolved,
+                    todo.message.trim_end_matches('\n')
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("")
+    }
+
+    async fn seed_journal(&mut self, path: &str) -> Result<(), StorageError> {
+        let archived = metadata(Journal::archive_path(path)).await.is_ok();
+        if archived || self.outbox.logged > 0 || self.todo_collection.is_empty() {
+            return Ok(());
+        }
+
+        let at = metadata(path)
+            .await
+            .and_then(|meta| meta.modified())
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_else(|_| Utc::now());
+        for (key, todo) in &self.todo_collection {
+            let entry = Entry {
+                at,
+                event: Event::TodoCreated {
+                    key: *key,
+                    todo: todo.clone(),
+                },
+            };
+            self.outbox.push(entry, (todo.id.clone(), todo.stamps));
+        }
+        Ok(())
+    }
+
+    fn store_files(path: &str) -> Vec<String> {
+        let name = GitRepo::file_name(path);
+        vec![
+            Journal::log_path(&name),
+            Journal::archive_path(&name),
+            History::path(&name),
+            Trash::path(&name),
+            Views::path(&name),
+            Vault::path(&name),
+            name,
+        ]
+    }
+
+    async fn commit(&mut self, path: &str, fallback: &str) -> Result<(), StorageError> {
+        self.git
+            .commit(path, &Self::store_files(path), fallback)
+            .await
+    }
+
+    async fn reload(&mut self, path: &str) -> Result<LoadReport, StorageError> {
+        self.replace_collection(BTreeMap::new());
+        self.length = 0;
+        self.history = History::default();
+        self.trash = Trash::default();
+        self.views = Views::default();
+        self.outbox = Outbox::default();
+        self.git.clear();
+        self.parse_file_for_todos(path).await
+    }
+
+    async fn discard(staged: &[String]) {
+        for file in staged {
+            let _ = remove_file(format!("{file}.tmp")).await;
+        }
+    }
+
+    fn watched(path: &str) -> Vec<String> {
+        vec![path.to_string(), Journal::log_path(path)]
+    }
+
+    async fn follow(&mut self, path: &str) -> Result<(), StorageError> {
+        if !self.watcher.is_changed(&Self::watched(path)).await {
+            return Ok(());
+        }
+
+        let reload = self.merge_external(path).await?;
+        self.watcher.absorb(reload);
+        Ok(())
+    }
+
+    async fn merge_external(&mut self, path: &str) -> Result<Reload, StorageError> {
+        let descriptions = self
+            .outbox
+            .pending
+            .iter()
+            .map(|entry| self.describe(&entry.event))
+            .collect::<Vec<String>>();
+        let Outbox {
+            pending, targets, ..
+        } = std::mem::take(&mut self.outbox);
+        let mut bases = HashMap::new();
+        for (id, stamps) in &targets {
+            bases.entry(id.clone()).or_insert(*stamps);
+        }
+        let history = std::mem::take(&mut self.history);
+        let views = std::mem::take(&mut self.views);
+        self.reload(path).await?;
+        self.history = history;
+        self.views = views;
+        let theirs = bases
+            .keys()
+            .filter_map(|id| {
+                let todo = self.todo_collection.get(&self.index.key_of(id)?)?;
+                Some((id.clone(), todo.clone()))
+            })
+            .collect::<HashMap<String, Todo>>();
+
+        let mut reload = Reload::default();
+        for ((entry, (id, _)), description) in pending.into_iter().zip(targets).zip(descriptions) {
+            let found = self.index.key_of(&id);
+            let clash = match (bases.get(&id), theirs.get(&id)) {
+                (Some(base), Some(theirs)) => entry.event.clashes(base, theirs),
+                _ => false,
+            };
+            match (entry.event, found) {
+                (Event::Renumbered { .. }, _) => {
+                    self.renumber();
+                    reload.merged += 1;
+                }
+                (event @ Event::Purged { .. }, _) => {
+                    self.emit(event);
+                    reload.merged += 1;
+                }
+                (Event::TodoCreated { .. }, Some(_))
+                | (Event::Removed { .. } | Event::Trashed { .. }, None) => {}
+                (Event::TodoCreated { key, todo }, None) => {
+                    let free = if self.todo_collection.contains_key(&key) {
+                        self.length + 1
+                    } else {
+                        key
+                    };
+                    if free != key {
+                        self.history.rekey(&id, free);
+                        reload.rekeyed.push((key, free));
+                    }
+                    self.emit(Event::TodoCreated { key: free, todo });
+                    reload.merged += 1;
+                }
+                (_, Some(_)) if clash => reload.conflicts.push(description),
+                (event, Some(key)) => {
+                    self.emit(event.with_key(key));
+                    reload.merged += 1;
+                }
+                (_, None) => reload.flagged.push(description),
+            }
+        }
+        Ok(reload)
+    }
+
+    fn keyed(&self, todos: Vec<Todo>) -> BTreeMap<u32, Todo> {
+        let keys = self
+            .todo_collection
+            .iter()
+            .map(|(key, todo)| (todo.id.clone(), *key))
+            .collect::<HashMap<String, u32>>();
+        let mut next = self.length;
+        let mut keyed = BTreeMap::new();
+        for todo in todos {
+            let key = keys.get(&todo.id).cloned().unwrap_or_else(|| {
+                next += 1;
+                next
+            });
+            keyed.insert(key, todo);
+        }
+        keyed
+    }
+
+    async fn todos_in(
+        &self,
+        repo: &GitRepo,
+        path: &str,
+        revision: &str,
+    ) -> Result<BTreeMap<u32, Todo>, StorageError> {
+        let name = GitRepo::file_name(path);
+        let snapshot = repo.show(revision, &name).await?.unwrap_or_default();
+        let log = repo
+            .show(revision, &GitRepo::file_name(&Journal::log_path(&name)))
+            .await?
+            .unwrap_or_default();
+
+        let at = |file: &str| format!("{revision}:{file}");
+        let snapshot = self
+            .codec
+            .decode(&snapshot)
+            .map_err(|err| err.at(&at(&name)))?;
+        let log = self
+            .codec
+            .decode(&log)
+            .map_err(|err| err.at(&at(&Journal::log_path(&name))))?;
+
+        let mut past = Todos::new();
+        past.replace_collection(Self::parse_snapshot(&at(&name), &snapshot)?);
+        let (entries, _) = Journal::parse(path, &log);
+        for entry in &entries {
+            past.apply(entry);
+        }
+        Ok(past.todo_collection)
+    }
+
+    async fn stored(&self, path: &str) -> Result<Option<BTreeMap<u32, Todo>>, StorageError> {
+        let Some(snapshot) = self.codec.read(path).await? else {
+            return Ok(None);
+        };
+        let mut stored = Todos::new();
+        stored.replace_collection(Self::parse_snapshot(path, &snapshot)?);
+        for entry in Journal::read(&Journal::log_path(path), &self.codec).await? {
+            stored.apply(&entry);
+        }
+        Ok(Some(stored.todo_collection))
+    }
+
+    async fn peer(&self, other: &str) -> Result<Todos, StorageError> {
+        let mut peer = Todos::new();
+        peer.codec = self.codec.clone();
+        peer.parse_file_for_todos(other).await?;
+        Ok(peer)
+    }
+
+    async fn load_trash(&mut self, path: &str) -> Result<(), StorageError> {
+        let trash_path = Trash::path(path);
+        let Some(trash) = self.codec.read(&trash_path).await? else {
+            return Ok(());
+        };
+        self.trash = serde_json::from_str(&trash).map_err(|err| {
+            StorageError::parse(format!("lixeira inválida ({err})")).at(&trash_path)
+        })?;
+        Ok(())
+    }
+
+    async fn load_views(&mut self, path: &str) -> Result<(), StorageError> {
+        let views_path = Views::path(path);
+        let Some(views) = self.codec.read(&views_path).await? else {
+            return Ok(());
+        };
+        self.views = serde_json::from_str(&views).map_err(|err| {
+            StorageError::parse(format!("visões inválidas ({err})")).at(&views_path)
+        })?;
+        Ok(())
+    }
+
+    fn expire_trash(&mut self) {
+        let expired = self.trash.expired(Utc::now(), self.retention);
+        if !expired.is_empty() {
+            self.emit(Event::Purged { ids: expired });
         }
     }
+
+    async fn load_history(&mut self, path: &str) -> Result<(), StorageError> {
+        let history_path = History::path(path);
+        let Some(history) = self.codec.read(&history_path).await? else {
+            return Ok(());
+        };
+        self.history = serde_json::from_str(&history).map_err(|err| {
+            StorageError::parse(format!("histórico inválido ({err})")).at(&history_path)
+        })?;
+        Ok(())
+    }
 }
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait::async_trait]
 pub trait TodoStorage {
-    fn insert_todo(&mut self, todo: Todo);
+    fn insert_todo(&mut self, todo: Todo) -> u32;
     fn update(&mut self, id: u32, new_todo: Todo) -> bool;
     fn get_one_todo(&self, key: u32) -> Option<Todo>;
     fn remove(&mut self, key: u32);
     fn is_empty(&self) -> usize;
-    fn get_collection(&self) -> &BTreeMap<u32, Todo>;
+    fn page(&self, query: &Query, sort: &Sort, number: usize, size: usize) -> Page;
+    fn search(&self, search: &Search) -> Vec<Hit>;
     fn resolve_one_todo(&mut self, key: u32) -> bool;
-    async fn parse_file_for_todos(&mut self, path: &str) -> Result<(), TerminalError>;
-    fn parse_line_for_todo(&mut self, line: &str) -> Result<(u32, String, bool), TerminalError>;
-    async fn parse_map_write_file(&mut self, path: &str) -> Result<(), TerminalError>;
+    fn undo(&mut self, steps: u32) -> u32;
+    fn renumber(&mut self) -> u32;
+    fn trashed(&self) -> Vec<TrashItem>;
+    fn restore_from_trash(&mut self, id: &str) -> Option<u32>;
+    fn empty_trash(&mut self) -> u32;
+    fn move_todo(&mut self, key: u32, to: Move) -> bool;
+    fn toggle_pin(&mut self, key: u32) -> Option<bool>;
+    fn stats(&self, now: DateTime<Utc>) -> Stats;
+    fn report(&self, from: NaiveDate, to: NaiveDate, grouping: Grouping) -> Report;
+    fn redo(&mut self, steps: u32) -> u32;
+    async fn todos_at(
+        &self,
+        path: &str,
+        moment: DateTime<Utc>,
+    ) -> Result<BTreeMap<u32, Todo>, StorageError>;
+    async fn list_backups(&self, path: &str) -> Result<Vec<Backup>, StorageError>;
+    async fn read_backup(
+        &mut self,
+        path: &str,
+        backup: &Backup,
+    ) -> Result<BTreeMap<u32, Todo>, StorageError>;
+    fn restore(&mut self, todos: BTreeMap<u32, Todo>) -> u32;
+    async fn reload_if_changed(&mut self, path: &str) -> Result<Option<Reload>, StorageError>;
+    async fn parse_file_for_todos(&mut self, path: &str) -> Result<LoadReport, StorageError>;
+    async fn parse_map_write_file(&mut self, path: &str) -> Result<(), StorageError>;
+}
+
+pub trait Storage: TodoStorage + ViewStorage + CryptoStorage + GitStorage + SyncStorage {}
+
+impl<T: TodoStorage + ViewStorage + CryptoStorage + GitStorage + SyncStorage> Storage for T {}
+
+#[derive(Clone)]
+pub struct Stores {
+    pub todos: Arc<Mutex<dyn TodoStorage + Send>>,
+    pub views: Arc<Mutex<dyn ViewStorage + Send>>,
+    pub crypto: Arc<Mutex<dyn CryptoStorage + Send>>,
+    pub git: Arc<Mutex<dyn GitStorage + Send>>,
+    pub sync: Arc<Mutex<dyn SyncStorage + Send>>,
+}
+
+impl Stores {
+    pub fn new<S: Storage + Send + 'static>(storage: S) -> Self {
+        let storage = Arc::new(Mutex::new(storage));
+        Self {
+            todos: storage.clone(),
+            views: storage.clone(),
+            crypto: storage.clone(),
+            git: storage.clone(),
+            sync: storage,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TodoStorage for Todos {
+    fn insert_todo(&mut self, todo: Todo) -> u32 {
+        self.length += 1;
+        let mut todo = todo;
+        if let Some(last) = self.index.last_place(false) {
+            if last >= todo.place(self.length) {
+                todo.position = Some(last + POSITION_GAP);
+            }
+        }
+        self.history.record(Operation::Insert {
+            key: self.length,
+            todo: todo.clone(),
+        });
+        self.emit(Event::TodoCreated {
+            key: self.length,
+            todo,
+        });
+        self.length
+    }
+
+    fn update(&mut self, id: u32, new_todo: Todo) -> bool {
+        let Some(before) = self.todo_collection.get(&id).cloned() else {
+            return false;
+        };
+        let mut new_todo = new_todo;
+        new_todo.id = before.id.clone();
+        new_todo.stamps = before.stamps;
+        new_todo.position = before.position;
+        new_todo.pinned = before.pinned;
+        self.history.record(Operation::Update {
+            key: id,
+            before,
+            after: new_todo.clone(),
+        });
+        self.set(id, Some(new_todo));
+        true
+    }
+
+    fn get_one_todo(&self, key: u32) -> Option<Todo> {
+        self.todo_collection.get(&key).cloned()
+    }
+
+    fn remove(&mut self, key: u32) {
+        if let Some(todo) = self.todo_collection.get(&key).cloned() {
+            self.history.record(Operation::Remove { key, todo });
+            self.emit(Event::Trashed { key });
+        }
+    }
+
+    fn is_empty(&self) -> usize {
+        self.todo_collection.len()
+    }
+
+    fn page(&self, query: &Query, sort: &Sort, number: usize, size: usize) -> Page {
+        let today = self.calendar.today(Local::now());
+        let (lookup, rest) = query.plan(today);
+        if rest.is_empty() && sort.is_manual() {
+            return self.page_of(
+                self.index.ordered(&lookup),
+                self.index.count(&lookup),
+                number,
+                size,
+            );
+        }
+        let mut keys = self
+            .index
+            .keys(&lookup)
+            .filter(|key| {
+                rest.is_empty()
+                    || self
+                        .todo_collection
+                        .get(key)
+                        .is_some_and(|todo| rest.matches(todo, today))
+            })
+            .collect::<Vec<u32>>();
+        keys.sort_by(
+            |a, b| match (self.todo_collection.get(a), self.todo_collection.get(b)) {
+                (Some(todo_a), Some(todo_b)) => sort.compare((*a, todo_a), (*b, todo_b)),
+                _ => a.cmp(b),
+            },
+        );
+        self.page_of(keys.iter().copied(), keys.len(), number, size)
+    }
+
+    fn resolve_one_todo(&mut self, key: u32) -> bool {
+        let Some(todo) = self.todo_collection.get(&key).cloned() else {
+            return false;
+        };
+        if !todo.resolved {
+            self.history
+                .record(Operation::Resolve { key, before: todo });
+            self.emit(Event::Resolved { key });
+        }
+        true
+    }
+
+    fn undo(&mut self, steps: u32) -> u32 {
+        let mut undone = 0;
+        while undone < steps {
+            let Some(operation) = self.history.undo() else {
+                break;
+            };
+            self.revert(&operation);
+            undone += 1;
+        }
+        undone
+    }
+
+    fn renumber(&mut self) -> u32 {
+        let moves = self
+            .todo_collection
+            .keys()
+            .zip(1..)
+            .filter(|(old, new)| *old != new)
+            .map(|(old, new)| (*old, new))
+            .collect::<Vec<(u32, u32)>>();
+        if moves.is_empty() {
+            return 0;
+        }
+
+        for (old, new) in &moves {
+            let id = self.todo_collection[old].id.clone();
+            self.history.rekey(&id, *new);
+        }
+        let renumbered = moves.len() as u32;
+        self.emit(Event::Renumbered { moves });
+        renumbered
+    }
+
+    fn search(&self, search: &Search) -> Vec<Hit> {
+        search.rank(self.todo_collection.iter())
+    }
+
+    fn trashed(&self) -> Vec<TrashItem> {
+        self.trash.items().iter().rev().cloned().collect()
+    }
+
+    fn restore_from_trash(&mut self, id: &str) -> Option<u32> {
+        let item = self.trash.get(id)?.clone();
+        let key = match self.todo_collection.contains_key(&item.key) {
+            true => self.length + 1,
+            false => item.key,
+        };
+        self.history.record(Operation::Restore {
+            key,
+            todo: item.todo.clone(),
+        });
+        self.emit(Event::TodoCreated {
+            key,
+            todo: item.todo,
+        });
+        Some(key)
+    }
+
+    fn empty_trash(&mut self) -> u32 {
+        let ids = self
+            .trash
+            .items()
+            .iter()
+            .map(|item| item.todo.id.clone())
+            .collect::<Vec<String>>();
+        if ids.is_empty() {
+            return 0;
+        }
+        let purged = ids.len() as u32;
+        self.emit(Event::Purged { ids });
+        purged
+    }
+
+    fn move_todo(&mut self, key: u32, to: Move) -> bool {
+        let Some(pinned) = self.todo_collection.get(&key).map(|todo| todo.pinned) else {
+            return false;
+        };
+        let Some(mut bounds) = self.bounds(key, pinned, to) else {
+            return false;
+        };
+        let mut operations = Vec::new();
+        if matches!(bounds, (Some(low), Some(high)) if high - low < 2) {
+            operations = self.respace(pinned);
+            bounds = self.bounds(key, pinned, to).unwrap_or_default();
+        }
+        let position = match bounds {
+            (Some(low), Some(high)) => low + (high - low) / 2,
+            (None, Some(high)) => high - POSITION_GAP,
+            (Some(low), None) => low + POSITION_GAP,
+            (None, None) => return false,
+        };
+
+        let before = self.todo_collection[&key].clone();
+        let mut after = before.clone();
+        after.position = Some(position);
+        operations.push(Operation::Update { key, before, after });
+        self.history.record(match operations.len() {
+            1 => operations.remove(0),
+            _ => Operation::Group { operations },
+        });
+        self.emit(Event::Moved { key, position });
+        true
+    }
+
+    fn toggle_pin(&mut self, key: u32) -> Option<bool> {
+        let todo = self.todo_collection.get(&key).cloned()?;
+        let pinned = !todo.pinned;
+        let mut after = todo.clone();
+        after.pinned = pinned;
+        self.history.record(Operation::Update {
+            key,
+            before: todo,
+            after,
+        });
+        self.emit(Event::Pinned { key, pinned });
+        Some(pinned)
+    }
+
+    fn stats(&self, now: DateTime<Utc>) -> Stats {
+        Stats::collect(self.todo_collection.iter(), self.views.list(), now)
+    }
+
+    fn report(&self, from: NaiveDate, to: NaiveDate, grouping: Grouping) -> Report {
+        let trashed = self
+            .trash
+            .items()
+            .iter()
+            .filter(|item| self.index.key_of(&item.todo.id).is_none())
+            .map(|item| (&item.key, &item.todo));
+        Report::collect(
+            self.todo_collection.iter().chain(trashed),
+            self.views.list(),
+            grouping,
+            from,
+            to,
+        )
+    }
+
+    fn redo(&mut self, steps: u32) -> u32 {
+        let mut redone = 0;
+        while redone < steps {
+            let Some(operation) = self.history.redo() else {
+                break;
+            };
+            self.replay(&operation);
+            redone += 1;
+        }
+        redone
+    }
+
+    async fn todos_at(
+        &self,
+        path: &str,
+        moment: DateTime<Utc>,
+    ) -> Result<BTreeMap<u32, Todo>, StorageError> {
+        let mut entries = Journal::read(&Journal::archive_path(path), &self.codec).await?;
+        entries.extend(Journal::read(&Journal::log_path(path), &self.codec).await?);
+        entries.extend(self.outbox.pending.iter().cloned());
+
+        let mut past = Todos::new();
+        for entry in entries.iter().filter(|entry| entry.at <= moment) {
+            past.apply(entry);
+        }
+        Ok(past.todo_collection)
+    }
+
+    async fn list_backups(&self, path: &str) -> Result<Vec<Backup>, StorageError> {
+        Backups::list(path).await
+    }
+
+    async fn read_backup(
+        &mut self,
+        path: &str,
+        backup: &Backup,
+    ) -> Result<BTreeMap<u32, Todo>, StorageError> {
+        let snapshot = Backups::read(path, backup, &self.codec).await?;
+        Self::parse_snapshot(&Backups::path(path, backup), &snapshot)
+    }
+
+    fn restore(&mut self, todos: BTreeMap<u32, Todo>) -> u32 {
+        let mut keys = self
+            .todo_collection
+            .keys()
+            .chain(todos.keys())
+            .cloned()
+            .collect::<Vec<u32>>();
+        keys.sort();
+        keys.dedup();
+
+        let mut restored = 0;
+        for key in keys {
+            let current = self.todo_collection.get(&key).cloned();
+            let target = todos.get(&key).cloned();
+            let operation = match (current, target.clone()) {
+                (None, Some(todo)) => Operation::Insert { key, todo },
+                (Some(todo), None) => Operation::Remove { key, todo },
+                (Some(before), Some(after))
+                    if before.id != after.id
+                        || before.message != after.message
+                        || before.notes != after.notes
+                        || before.resolved != after.resolved
+                        || before.place(key) != after.place(key)
+                        || before.pinned != after.pinned =>
+                {
+                    Operation::Update { key, before, after }
+                }
+                _ => continue,
+            };
+            self.history.record(operation);
+            self.set(key, target);
+            restored += 1;
+        }
+        restored
+    }
+
+    async fn reload_if_changed(&mut self, path: &str) -> Result<Option<Reload>, StorageError> {
+        self.follow(path).await?;
+        Ok(self.watcher.take())
+    }
+
+    async fn parse_file_for_todos(&mut self, path: &str) -> Result<LoadReport, StorageError> {
+        let mut report = LoadReport::default();
+        let mut todos = BTreeMap::new();
+        let mut rejected = Vec::new();
+        let lenient = self.lenient;
+        let found = self
+            .codec
+            .read_lines(path, |number, line| {
+                let rejected = lenient.then_some(&mut rejected);
+                Self::read_snapshot_line(path, number, line, &mut todos, rejected)
+            })
+            .await?;
+        if !found {
+            if let Some(dir) = Path::new(path).parent() {
+                create_dir_all(dir)
+                    .await
+                    .map_err(|err| StorageError::io(path, err))?;
+            }
+            write(path, b"")
+                .await
+                .map_err(|err| StorageError::io(path, err))?;
+            report.created = true;
+        }
+        self.replace_collection(todos);
+        self.load_trash(path).await?;
+
+        let log_path = Journal::log_path(path);
+        let entries = if self.lenient {
+            let (entries, log_rejected) = Journal::read_lenient(&log_path, &self.codec).await?;
+            rejected.extend(log_rejected);
+            report.rejected = rejected;
+            entries
+        } else {
+            Journal::read(&log_path, &self.codec).await?
+        };
+        for entry in &entries {
+            self.apply(entry);
+        }
+        self.outbox.logged = entries.len();
+
+        self.length = self
+            .todo_collection
+            .keys()
+            .next_back()
+            .cloned()
+            .unwrap_or(0)
+            .max(self.length);
+        self.seed_journal(path).await?;
+        self.expire_trash();
+
+        if !report.rejected.is_empty() {
+            report.quarantine = Some(Quarantine::store(path, &report.rejected, &self.codec).await?);
+            Journal::compact(path, &self.snapshot(), &self.codec).await?;
+            self.outbox.logged = 0;
+        }
+
+        self.load_history(path).await?;
+        self.load_views(path).await?;
+        self.watcher.mark(&Self::watched(path)).await;
+        if self.git.is_enabled() {
+            GitRepo::new(path).init().await?;
+            self.commit(path, "init todo list").await?;
+        }
+        Ok(report)
+    }
+
+    async fn parse_map_write_file(&mut self, path: &str) -> Result<(), StorageError> {
+        self.follow(path).await?;
+        if self
+            .backups
+            .as_ref()
+            .is_some_and(|backups| backups.is_due(Utc::now()))
+        {
+            if let Some(stored) = self.stored(path).await? {
+                let snapshot = Self::snapshot_of(&stored);
+                if let Some(backups) = &mut self.backups {
+                    backups.save(path, &snapshot, &self.codec).await?;
+                }
+            }
+        }
+
+        Journal::append(&Journal::log_path(path), &self.outbox.pending, &self.codec).await?;
+        self.outbox.sent();
+
+        let snapshot_missing = matches!(
+            metadata(path).await,
+            Err(err) if err.kind() == ErrorKind::NotFound
+        );
+        if snapshot_missing || self.outbox.logged >= COMPACT_AFTER {
+            Journal::compact(path, &self.snapshot(), &self.codec).await?;
+            self.outbox.logged = 0;
+        }
+
+        let history = serde_json::to_string(&self.history).map_err(|err| {
+            StorageError::parse(format!("erro ao salvar o histórico ({err})")).at(path)
+        })?;
+        self.codec.write(&History::path(path), &history).await?;
+        let trash = serde_json::to_string(&self.trash).map_err(|err| {
+            StorageError::parse(format!("erro ao salvar a lixeira ({err})")).at(path)
+        })?;
+        self.codec.write(&Trash::path(path), &trash).await?;
+        let views = serde_json::to_string(&self.views).map_err(|err| {
+            StorageError::parse(format!("erro ao salvar as visões ({err})")).at(path)
+        })?;
+        self.codec.write(&Views::path(path), &views).await?;
+        self.watcher.mark(&Self::watched(path)).await;
+        self.commit(path, "update todo list").await
+    }
 }
 
-#[async_trait::async_trait]
-impl TodoStorage for Todos {
-    fn insert_todo(&mut self, todo: Todo) {
-        self.length += 1;
-        self.todo_collection.entry(self.length).or_insert(todo);
+impl ViewStorage for Todos {
+    fn views(&self) -> Vec<View> {
+        self.views.list().to_vec()
     }
 
-    fn update(&mut self, id: u32, new_todo: Todo) -> bool {
-        if let std::collections::btree_map::Entry::Occupied(mut e) = self.todo_collection.entry(id)
-        {
-            e.insert(new_todo);
-            return true;
-        }
-        false
+    fn save_view(&mut self, view: View) {
+        self.views.save(view);
     }
 
-    fn get_one_todo(&self, key: u32) -> Option<Todo> {
-        self.todo_collection.get(&key).cloned()
+    fn delete_view(&mut self, name: &str) -> bool {
+        self.views.remove(name)
     }
 
-    fn remove(&mut self, key: u32) {
-        self.todo_collection.remove(&key);
+    fn sort_for(&self, list: &str) -> Sort {
+        self.views.sort_for(list)
     }
 
-    fn is_empty(&self) -> usize {
-        self.todo_collection.len()
+    fn remember_sort(&mut self, list: &str, sort: Sort) -> bool {
+        self.views.remember_sort(list, sort)
     }
+}
 
-    fn get_collection(&self) -> &BTreeMap<u32, Todo> {
-        &self.todo_collection
+#[async_trait::async_trait]
+impl CryptoStorage for Todos {
+    async fn is_encrypted(&self, path: &str) -> bool {
+        Vault::is_encrypted(path).await
     }
 
-    fn resolve_one_todo(&mut self, key: u32) -> bool {
-        let Some(todo) = self.todo_collection.get_mut(&key) else {
-            return false;
-        };
-        todo.resolved = true;
-        true
+    async fn unlock(&mut self, path: &str, passphrase: &str) -> Result<(), StorageError> {
+        self.codec = Vault::unlock(path, passphrase).await?;
+        Ok(())
     }
 
-    async fn parse_file_for_todos(&mut self, path: &str) -> Result<(), TerminalError> {
-        let todo_file = read_to_string(path)
-            .await
-            .map_err(TerminalError::StdinErr)?;
+    async fn change_passphrase(
+        &mut self,
+        path: &str,
+        passphrase: &str,
+    ) -> Result<(), StorageError> {
+        let mut files = vec![
+            path.to_string(),
+            Journal::log_path(path),
+            Journal::archive_path(path),
+            History::path(path),
+            Trash::path(path),
+            Views::path(path),
+            Quarantine::path(path),
+        ];
+        for backup in Backups::list(path).await? {
+            files.push(Backups::path(path, &backup));
+        }
 
-        for line in todo_file.lines() {
-            let (key, todo_message, resolve) = self.parse_line_for_todo(line)?;
-            self.todo_collection.entry(key).or_insert(Todo {
-                message: format!("{todo_message}\n"),
-                resolved: resolve,
-            });
+        let mut contents = Vec::new();
+        for file in files {
+            if let Some(content) = self.codec.read(&file).await? {
+                contents.push((file, content));
+            }
         }
 
-        self.length = self
-            .todo_collection
-            .keys()
-            .cloned()
-            .collect::<Vec<u32>>()
-            .pop()
-            .unwrap_or(0);
-        Ok(())
+        let (codec, header) = Vault::seal(passphrase)?;
+        let mut staged = Vec::new();
+        for (file, content) in &contents {
+            staged.push(file.clone());
+            if let Err(err) = codec.write(&format!("{file}.tmp"), content).await {
+                Self::discard(&staged).await;
+                return Err(err);
+            }
+        }
+        staged.push(Vault::path(path));
+        if let Err(err) = Codec::Plain
+            .write(&format!("{}.tmp", Vault::path(path)), &header)
+            .await
+        {
+            Self::discard(&staged).await;
+            return Err(err);
+        }
+
+        for file in &staged {
+            rename(format!("{file}.tmp"), file)
+                .await
+                .map_err(|err| StorageError::io(file, err))?;
+        }
+        self.codec = codec;
+        self.watcher.mark(&Self::watched(path)).await;
+        self.commit(path, "change passphrase").await
     }
+}
 
-    fn parse_line_for_todo(&mut self, line: &str) -> Result<(u32, String, bool), TerminalError> {
-        let mut text_slice = line.split('-');
-        let key: u32 = text_slice
-            .next()
-            .ok_or_else(|| {
-                TerminalError::NotFound("Erro no parse_line [key not found]".to_string())
-            })?
-            .parse()
-            .map_err(TerminalError::ParseErr)?;
+#[async_trait::async_trait]
+impl GitStorage for Todos {
+    async fn git_log(&self, path: &str) -> Result<Vec<Commit>, StorageError> {
+        self.git.repo(path)?.log(&Self::store_files(path), 20).await
+    }
 
-        let resolve = matches!(
-            text_slice.next().ok_or_else(|| TerminalError::NotFound(
-                "Erro no parse_line [resolve not found]".to_string()
-            ))?,
-            "true"
-        );
+    async fn revert_to(&mut self, path: &str, commit: &Commit) -> Result<u32, StorageError> {
+        let repo = self.git.repo(path)?;
+        let past = self.todos_in(&repo, path, &commit.id).await?;
+        let restored = self.restore(past);
+        self.git
+            .replace(format!("revert to {}: {}", commit.id, commit.message));
+        Ok(restored)
+    }
 
-        let message = text_slice.collect::<Vec<&str>>().join("-");
+    async fn git_sync(&mut self, path: &str) -> Result<(), StorageError> {
+        let repo = self.git.repo(path)?;
+        self.parse_map_write_file(path).await?;
+        match repo.fetch().await? {
+            Upstream::Missing | Upstream::UpToDate => {}
+            Upstream::Behind => {
+                repo.fast_forward().await?;
+                let reload = self.merge_external(path).await?;
+                self.watcher.absorb(reload);
+            }
+            Upstream::Diverged => {
+                let base = self
+                    .todos_in(&repo, path, &repo.merge_base().await?)
+                    .await?;
+                let theirs = self.todos_in(&repo, path, "@{u}").await?;
+                let merge = Merge::new(&base, &self.todo_collection, &theirs);
+                if !merge.conflicts.is_empty() {
+                    return Err(StorageError::git(format!(
+                        "a lista local e a remota divergiram com {} conflito(s); faça o merge manualmente com git",
+                        merge.conflicts.len()
+                    ))
+                    .at(path));
+                }
+                let merged = self.keyed(merge.todos);
+                self.restore(merged);
+                self.git.replace("merge remote changes".to_string());
+                self.parse_map_write_file(path).await?;
+                repo.merge_ours("merge remote changes").await?;
+            }
+        }
+        repo.push().await
+    }
+}
 
-        Ok((key, message, resolve))
+#[async_trait::async_trait]
+impl SyncStorage for Todos {
+    async fn plan_sync(&mut self, path: &str, other: &str) -> Result<Merge, StorageError> {
+        let base_path = Merge::base_path(path);
+        let base = match self.codec.read(&base_path).await? {
+            Some(base) => Self::parse_snapshot(&base_path, &base)?,
+            None => BTreeMap::new(),
+        };
+        let theirs = self.peer(other).await?;
+        Ok(Merge::new(
+            &base,
+            &self.todo_collection,
+            &theirs.todo_collection,
+        ))
     }
 
-    async fn parse_map_write_file(&mut self, path: &str) -> Result<(), TerminalError> {
-        let todo_string = self
-            .todo_collection
-            .iter()
-            .map(|(key, todo)| format!("{key}-{}-{}", todo.resolved, todo.message))
-            .collect::<Vec<String>>()
-            .join("");
+    async fn apply_sync(
+        &mut self,
+        path: &str,
+        other: &str,
+        merge: Merge,
+    ) -> Result<u32, StorageError> {
+        let merged = self.keyed(merge.todos);
+        let mut theirs = self.peer(other).await?;
+        theirs.restore(merged.clone());
+        theirs.parse_map_write_file(other).await?;
 
-        write(path, todo_string.as_bytes())
-            .await
-            .map_err(TerminalError::StdoutErr)?;
-        Ok(())
+        let restored = self.restore(merged);
+        self.parse_map_write_file(path).await?;
+        self.codec
+            .write(
+                &Merge::base_path(path),
+                &Self::snapshot_of(&self.todo_collection),
+            )
+            .await?;
+        Ok(restored)
     }
 }
 
@@ -145,14 +1432,28 @@ mod tests {
         fn create_collection() -> BTreeMap<u32, Todo> {
             let mut list = BTreeMap::<u32, Todo>::new();
             list.entry(1).or_insert(factori::create!(Todo));
-            list.entry(2).or_insert(Todo::new("boo".to_string()));
+            let mut boo = Todo::new("boo".to_string());
+            boo.id = "boo".to_string();
+            list.entry(2).or_insert(boo);
             list
         }
 
         factori::factori!(Todos, {
             default {
                 todo_collection = create_collection(),
+                index = Index::build(&create_collection()),
                 length = 2,
+                history = History::default(),
+                trash = Trash::default(),
+                views = Views::default(),
+                retention = chrono::Duration::days(TRASH_DAYS),
+                calendar = Calendar::default(),
+                outbox = Outbox::default(),
+                backups = None,
+                lenient = false,
+                codec = Codec::Plain,
+                git = Versioning::default(),
+                watcher = Watcher::default(),
             }
         });
     }
@@ -181,42 +1482,780 @@ mod tests {
     #[test]
     fn test_remove_todo_in_storage() {
         let mut storage = factori::create!(Todos);       
-        assert_eq!(storage.get_collection().len(), 2);
+        assert_eq!(storage.todo_collection.len(), 2);
         assert_eq!(&storage.get_one_todo(1).unwrap().message, "foo");
         storage.remove(1);        
-        assert_eq!(storage.get_collection().len(), 1);
+        assert_eq!(storage.todo_collection.len(), 1);
     }
 
     #[test]
     fn test_resolve_one_todo_is_ok() {
         let mut storage = factori::create!(Todos);        
-        assert_eq!(storage.get_one_todo(1).unwrap().resolved, false);
+        assert!(!storage.get_one_todo(1).unwrap().resolved);
         storage.resolve_one_todo(1);
-        assert_eq!(storage.get_one_todo(1).unwrap().resolved, true);
+        assert!(storage.get_one_todo(1).unwrap().resolved);
     }
 
     #[tokio::test]
     async fn test_parse_file_for_todos() {
         let mut storage = Todos::new();
-        tokio::fs::write("test_todo.txt", "1-false-foo\n".as_bytes())
+        tokio::fs::write("test_parse_todo.txt", "1-false-foo\n".as_bytes())
             .await
             .unwrap();
         assert_eq!(storage.length, 0);
-        storage.parse_file_for_todos("test_todo.txt").await.unwrap();
+        storage
+            .parse_file_for_todos("test_parse_todo.txt")
+            .await
+            .unwrap();
         let todo = storage.get_one_todo(1).unwrap();
         assert!(!todo.resolved);
         assert_eq!(todo.message, "foo\n");
         assert_eq!(storage.length, 1);
-        tokio::fs::remove_file("test_todo.txt").await.unwrap();
+        tokio::fs::remove_file("test_parse_todo.txt").await.unwrap();
     }
 
     #[tokio::test]
     async fn test_parse_map_write_file() {
         let mut storage = factori::create!(Todos);        
-        storage.parse_map_write_file("test_todo.txt").await.unwrap();
-        let test_todo = tokio::fs::read_to_string("test_todo.txt").await.unwrap();
+        storage.parse_map_write_file("test_write_todo.txt").await.unwrap();
+        let test_todo = tokio::fs::read_to_string("test_write_todo.txt").await.unwrap();
         assert!(test_todo.contains("1-false-foo"));
         assert!(test_todo.contains("2-false-boo"));        
-        tokio::fs::remove_file("test_todo.txt").await.unwrap();
+        remove_store("test_write_todo.txt").await;
+    }
+
+    #[test]
+    fn test_undo_and_redo_operations() {
+        let mut storage = factori::create!(Todos);
+        storage.insert_todo(Todo::new("Lorem".to_string()));
+        storage.resolve_one_todo(1);
+        storage.remove(2);
+
+        assert_eq!(storage.undo(2), 2);
+        assert!(storage.get_one_todo(2).is_some());
+        assert!(!storage.get_one_todo(1).unwrap().resolved);
+        assert!(storage.get_one_todo(3).is_some());
+
+        assert_eq!(storage.redo(5), 2);
+        assert!(storage.get_one_todo(1).unwrap().resolved);
+        assert!(storage.get_one_todo(2).is_none());
+
+        assert_eq!(storage.undo(5), 3);
+        assert!(storage.get_one_todo(3).is_none());
+        assert_eq!(storage.undo(1), 0);
+    }
+
+    #[tokio::test]
+    async fn test_history_is_persisted() {
+        let mut storage = factori::create!(Todos);
+        storage.update(1, Todo::new("Lorem\n".to_string()));
+        storage
+            .parse_map_write_file("test_history_todo.txt")
+            .await
+            .unwrap();
+
+        let mut storage = Todos::new();
+        storage
+            .parse_file_for_todos("test_history_todo.txt")
+            .await
+            .unwrap();
+        assert_eq!(storage.undo(1), 1);
+        assert_eq!(&storage.get_one_todo(1).unwrap().message, "foo");
+        remove_store("test_history_todo.txt").await;
+    }
+
+    #[tokio::test]
+    async fn test_changes_are_replayed_from_event_log() {
+        let path = "test_events_todo.txt";
+        let mut storage = Todos::new();
+        storage.parse_map_write_file(path).await.unwrap();
+
+        storage.insert_todo(Todo::new("foo\n".to_string()));
+        storage.insert_todo(Todo::new("boo\n".to_string()));
+        storage.resolve_one_todo(1);
+        storage.update(2, Todo::new("Lorem\n".to_string()));
+        storage.parse_map_write_file(path).await.unwrap();
+        assert_eq!(tokio::fs::read_to_string(path).await.unwrap(), "");
+        assert_eq!(storage.outbox.logged, 4);
+
+        let mut storage = Todos::new();
+        storage.parse_file_for_todos(path).await.unwrap();
+        assert!(storage.get_one_todo(1).unwrap().resolved);
+        assert_eq!(&storage.get_one_todo(2).unwrap().message, "Lorem\n");
+        assert_eq!(storage.length, 2);
+        remove_store(path).await;
+    }
+
+    #[tokio::test]
+    async fn test_todos_at_past_moment() {
+        let path = "test_past_todo.txt";
+        tokio::fs::write(path, "1-false-foo\n".as_bytes())
+            .await
+            .unwrap();
+        let mut storage = Todos::new();
+        storage.parse_file_for_todos(path).await.unwrap();
+        assert_eq!(storage.outbox.pending.len(), 1);
+        let before_changes = Utc::now();
+
+        storage.resolve_one_todo(1);
+        storage.insert_todo(Todo::new("boo\n".to_string()));
+        storage.parse_map_write_file(path).await.unwrap();
+
+        let past = storage.todos_at(path, before_changes).await.unwrap();
+        assert_eq!(past.len(), 1);
+        assert!(!past.get(&1).unwrap().resolved);
+
+        let present = storage.todos_at(path, Utc::now()).await.unwrap();
+        assert_eq!(present.len(), 2);
+        assert!(present.get(&1).unwrap().resolved);
+        remove_store(path).await;
+    }
+
+    #[tokio::test]
+    async fn test_restore_backup() {
+        let path = "test_restore_todo.txt";
+        let mut storage = factori::create!(Todos).with_backups(BackupPolicy::default());
+        storage.update(1, Todo::new("foo\n".to_string()));
+        storage.update(2, Todo::new("boo\n".to_string()));
+        storage.parse_map_write_file(path).await.unwrap();
+        storage.remove(1);
+        storage.resolve_one_todo(2);
+        storage.parse_map_write_file(path).await.unwrap();
+
+        let backups = storage.list_backups(path).await.unwrap();
+        assert_eq!(backups.len(), 1);
+        let todos = storage.read_backup(path, &backups[0]).await.unwrap();
+        assert_eq!(todos.len(), 2);
+
+        assert_eq!(storage.restore(todos), 2);
+        assert_eq!(&storage.get_one_todo(1).unwrap().message, "foo\n");
+        assert!(!storage.get_one_todo(2).unwrap().resolved);
+        assert_eq!(storage.undo(2), 2);
+        assert!(storage.get_one_todo(1).is_none());
+        tokio::fs::remove_dir_all(Backups::dir(path)).await.unwrap();
+        remove_store(path).await;
+    }
+
+    #[tokio::test]
+    async fn test_first_run_creates_store() {
+        let path = "test_first_run/todo_list.txt";
+        let mut storage = Todos::new();
+        let report = storage.parse_file_for_todos(path).await.unwrap();
+        assert!(report.created);
+        assert_eq!(tokio::fs::read_to_string(path).await.unwrap(), "");
+        tokio::fs::remove_dir_all("test_first_run").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_lenient_load_quarantines_bad_lines() {
+        let path = "test_lenient_todo.txt";
+        tokio::fs::write(path, "1-false-foo\nx-false-boo\n3-tru-Lorem\n4-true-ok\n")
+            .await
+            .unwrap();
+
+        let error = Todos::new().parse_file_for_todos(path).await.err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "linha 2 de test_lenient_todo.txt: chave inválida 'x'"
+        );
+
+        let mut storage = Todos::new().with_lenient(true);
+        let report = storage.parse_file_for_todos(path).await.unwrap();
+        assert_eq!(report.rejected.len(), 2);
+        assert_eq!(report.rejected[0].line, 2);
+        assert!(report.rejected[1].reason.contains("tru"));
+        assert_eq!(storage.todo_collection.len(), 2);
+
+        let quarantine = tokio::fs::read_to_string(Quarantine::path(path))
+            .await
+            .unwrap();
+        assert!(quarantine.contains("x-false-boo"));
+        assert!(Todos::new().parse_file_for_todos(path).await.is_ok());
+        tokio::fs::remove_file(Quarantine::path(path))
+            .await
+            .unwrap();
+        remove_store(path).await;
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_store_roundtrip() {
+        let path = "test_encrypted_todo.txt";
+        let mut storage = factori::create!(Todos);
+        storage.update(1, Todo::new("Cliente Fulano\n".to_string()));
+        storage.parse_map_write_file(path).await.unwrap();
+        storage.change_passphrase(path, "segredo").await.unwrap();
+        storage.insert_todo(Todo::new("Cliente Beltrano\n".to_string()));
+        storage.parse_map_write_file(path).await.unwrap();
+
+        let blocked = format!("{}.tmp", History::path(path));
+        tokio::fs::create_dir(&blocked).await.unwrap();
+        assert!(storage.change_passphrase(path, "nova").await.is_err());
+        tokio::fs::remove_dir(&blocked).await.unwrap();
+        assert!(tokio::fs::metadata(format!("{path}.tmp")).await.is_err());
+
+        for file in [
+            path.to_string(),
+            Journal::log_path(path),
+            History::path(path),
+        ] {
+            let content = tokio::fs::read_to_string(file).await.unwrap();
+            assert!(!content.contains("Cliente"));
+        }
+
+        let mut storage = Todos::new();
+        assert!(storage.is_encrypted(path).await);
+        assert!(storage.unlock(path, "errada").await.is_err());
+        storage.unlock(path, "segredo").await.unwrap();
+        storage.parse_file_for_todos(path).await.unwrap();
+        assert_eq!(
+            &storage.get_one_todo(3).unwrap().message,
+            "Cliente Beltrano\n"
+        );
+        tokio::fs::remove_file(Vault::path(path)).await.unwrap();
+        remove_store(path).await;
+    }
+
+    #[tokio::test]
+    async fn test_git_storage_commits_and_syncs() {
+        let root = std::env::temp_dir().join(format!("todo_git_test_{}", std::process::id()));
+        let _ = tokio::fs::remove_dir_all(&root).await;
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .current_dir(&root)
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(status.status.success(), "{:?}", status);
+            String::from_utf8_lossy(&status.stdout).to_string()
+        };
+        tokio::fs::create_dir_all(root.join("store")).await.unwrap();
+        git(&["init", "--quiet", "--bare", "remote.git"]);
+
+        let path = root
+            .join("store/todo_list.txt")
+            .to_string_lossy()
+            .to_string();
+        let mut storage = Todos::new().with_git(true);
+        storage.parse_file_for_todos(&path).await.unwrap();
+        git(&["-C", "store", "remote", "add", "origin", "../remote.git"]);
+
+        storage.insert_todo(Todo::new("Buy milk\n".to_string()));
+        storage.parse_map_write_file(&path).await.unwrap();
+        storage.resolve_one_todo(1);
+        storage.parse_map_write_file(&path).await.unwrap();
+
+        let commits = storage.git_log(&path).await.unwrap();
+        assert_eq!(commits.len(), 3);
+        assert_eq!(commits[0].message, "resolve #1: Buy milk");
+        assert_eq!(commits[1].message, "add #1: Buy milk");
+
+        storage.git_sync(&path).await.unwrap();
+        git(&["clone", "--quiet", "remote.git", "clone"]);
+        let cloned = tokio::fs::read_to_string(root.join("clone/todo_list.txt.log"))
+            .await
+            .unwrap();
+        assert!(cloned.contains("Buy milk"));
+
+        assert_eq!(storage.revert_to(&path, &commits[1]).await.unwrap(), 1);
+        storage.parse_map_write_file(&path).await.unwrap();
+        assert!(!storage.get_one_todo(1).unwrap().resolved);
+        let commits = storage.git_log(&path).await.unwrap();
+        assert!(commits[0].message.starts_with("revert to"));
+        tokio::fs::remove_dir_all(&root).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_git_sync_keeps_local_changes_and_merges_diverged_copies() {
+        let root = std::env::temp_dir().join(format!("todo_git_sync_{}", std::process::id()));
+        let _ = tokio::fs::remove_dir_all(&root).await;
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .current_dir(&root)
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(status.status.success(), "{:?}", status);
+        };
+        tokio::fs::create_dir_all(root.join("mine")).await.unwrap();
+        git(&["init", "--quiet", "--bare", "remote.git"]);
+
+        let mine = root
+            .join("mine/todo_list.txt")
+            .to_string_lossy()
+            .to_string();
+        let mut storage = Todos::new().with_git(true);
+        storage.parse_file_for_todos(&mine).await.unwrap();
+        git(&["-C", "mine", "remote", "add", "origin", "../remote.git"]);
+        storage.insert_todo(Todo::new("Buy milk\n".to_string()));
+        storage.git_sync(&mine).await.unwrap();
+
+        git(&["clone", "--quiet", "remote.git", "theirs"]);
+        let theirs = root
+            .join("theirs/todo_list.txt")
+            .to_string_lossy()
+            .to_string();
+        let mut other = Todos::new().with_git(true);
+        other.parse_file_for_todos(&theirs).await.unwrap();
+        other.insert_todo(Todo::new("Bake bread\n".to_string()));
+        other.git_sync(&theirs).await.unwrap();
+
+        storage.insert_todo(Todo::new("Buy eggs\n".to_string()));
+        storage.save_view(View::new("hoje", "status:open"));
+        storage.git_sync(&mine).await.unwrap();
+        assert_eq!(storage.is_empty(), 3);
+        assert_eq!(storage.undo(1), 1);
+        assert_eq!(storage.is_empty(), 2);
+        assert_eq!(storage.redo(1), 1);
+        storage.git_sync(&mine).await.unwrap();
+
+        other.git_sync(&theirs).await.unwrap();
+        let messages = (1..=3)
+            .filter_map(|key| other.get_one_todo(key))
+            .map(|todo| todo.message)
+            .collect::<Vec<String>>();
+        assert_eq!(messages, ["Buy milk\n", "Buy eggs\n", "Bake bread\n"]);
+        assert_eq!(storage.views().len(), 1);
+
+        storage.resolve_one_todo(1);
+        storage.git_sync(&mine).await.unwrap();
+        other.remove(1);
+        let error = other.git_sync(&theirs).await.err().unwrap();
+        assert!(error.to_string().contains("divergiram com 1 conflito"));
+        tokio::fs::remove_dir_all(&root).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sync_merges_two_copies() {
+        let (mine, theirs) = ("test_sync_mine.txt", "test_sync_theirs.txt");
+        let mut storage = factori::create!(Todos);
+        storage.parse_map_write_file(mine).await.unwrap();
+        let mut storage = Todos::new();
+        storage.parse_file_for_todos(mine).await.unwrap();
+        let merge = storage.plan_sync(mine, theirs).await.unwrap();
+        assert!(merge.conflicts.is_empty());
+        storage.apply_sync(mine, theirs, merge).await.unwrap();
+
+        let mut other = Todos::new();
+        other.parse_file_for_todos(theirs).await.unwrap();
+        assert_eq!(other.get_one_todo(1).unwrap().id, "foo");
+        other.resolve_one_todo(1);
+        other.insert_todo(Todo::new("bar\n".to_string()));
+        other.parse_map_write_file(theirs).await.unwrap();
+
+        storage.update(2, Todo::new("baz\n".to_string()));
+        let merge = storage.plan_sync(mine, theirs).await.unwrap();
+        assert!(merge.conflicts.is_empty());
+        assert_eq!(storage.apply_sync(mine, theirs, merge).await.unwrap(), 2);
+        assert!(storage.get_one_todo(1).unwrap().resolved);
+        assert_eq!(&storage.get_one_todo(2).unwrap().message, "baz\n");
+        assert_eq!(&storage.get_one_todo(3).unwrap().message, "bar\n");
+
+        let mut other = Todos::new();
+        other.parse_file_for_todos(theirs).await.unwrap();
+        assert_eq!(&other.get_one_todo(2).unwrap().message, "baz\n");
+        other.update(3, Todo::new("bar theirs\n".to_string()));
+        other.parse_map_write_file(theirs).await.unwrap();
+        storage.update(3, Todo::new("bar mine\n".to_string()));
+        let merge = storage.plan_sync(mine, theirs).await.unwrap();
+        assert_eq!(merge.conflicts.len(), 1);
+
+        for path in [mine, theirs] {
+            let _ = tokio::fs::remove_file(Merge::base_path(path)).await;
+            remove_store(path).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_external_changes_are_reloaded_and_merged() {
+        let path = "test_live_reload_todo.txt";
+        let mut storage = factori::create!(Todos);
+        storage.parse_map_write_file(path).await.unwrap();
+        let mut storage = Todos::new();
+        storage.parse_file_for_todos(path).await.unwrap();
+        assert!(storage.reload_if_changed(path).await.unwrap().is_none());
+
+        let mut editor = Todos::new();
+        editor.parse_file_for_todos(path).await.unwrap();
+        editor.insert_todo(Todo::new("bar\n".to_string()));
+        editor.remove(1);
+        editor.parse_map_write_file(path).await.unwrap();
+
+        storage.insert_todo(Todo::new("baz\n".to_string()));
+        storage.resolve_one_todo(1);
+        storage.parse_map_write_file(path).await.unwrap();
+        let reload = storage.reload_if_changed(path).await.unwrap().unwrap();
+        assert_eq!(reload.rekeyed, vec![(3, 4)]);
+        assert_eq!(reload.flagged.len(), 1);
+        assert_eq!(&storage.get_one_todo(3).unwrap().message, "bar\n");
+        assert_eq!(&storage.get_one_todo(4).unwrap().message, "baz\n");
+        assert!(storage.get_one_todo(1).is_none());
+
+        let mut reloaded = Todos::new();
+        reloaded.parse_file_for_todos(path).await.unwrap();
+        assert_eq!(reloaded.is_empty(), 3);
+        assert!(storage.reload_if_changed(path).await.unwrap().is_none());
+        remove_store(path).await;
+    }
+
+    #[tokio::test]
+    async fn test_views_survive_an_external_reload() {
+        let path = "test_live_views_todo.txt";
+        let mut storage = factori::create!(Todos);
+        storage.parse_map_write_file(path).await.unwrap();
+
+        let mut editor = Todos::new();
+        editor.parse_file_for_todos(path).await.unwrap();
+        editor.insert_todo(Todo::new("bar\n".to_string()));
+        editor.parse_map_write_file(path).await.unwrap();
+
+        let sort = Sort {
+            primary: crate::sort::SortKey::Due,
+            ..Sort::default()
+        };
+        storage.save_view(View::new("hoje", "status:open due<=hoje"));
+        storage.remember_sort("hoje", sort);
+        storage.parse_map_write_file(path).await.unwrap();
+        assert_eq!(storage.views().len(), 1);
+        assert!(storage.sort_for("hoje") == sort);
+        assert_eq!(&storage.get_one_todo(3).unwrap().message, "bar\n");
+
+        let mut reloaded = Todos::new();
+        reloaded.parse_file_for_todos(path).await.unwrap();
+        assert_eq!(reloaded.views().len(), 1);
+        assert!(reloaded.sort_for("hoje") == sort);
+        remove_store(path).await;
+    }
+
+    #[tokio::test]
+    async fn test_external_edits_to_the_same_todo_are_kept() {
+        let path = "test_live_conflict_todo.txt";
+        let mut storage = Todos::new();
+        storage.parse_file_for_todos(path).await.unwrap();
+        storage.insert_todo(Todo::new("pão\n".to_string()));
+        storage.insert_todo(Todo::new("leite\n".to_string()));
+        storage.parse_map_write_file(path).await.unwrap();
+
+        let mut editor = Todos::new();
+        editor.parse_file_for_todos(path).await.unwrap();
+        for (key, notes) in [(1, "integral"), (2, "desnatado")] {
+            let mut todo = editor.get_one_todo(key).unwrap();
+            todo.notes = notes.to_string();
+            editor.update(key, todo);
+        }
+        editor.parse_map_write_file(path).await.unwrap();
+
+        let mut bread = storage.get_one_todo(1).unwrap();
+        bread.notes = "francês".to_string();
+        storage.update(1, bread);
+        storage.resolve_one_todo(1);
+        storage.remove(2);
+        storage.parse_map_write_file(path).await.unwrap();
+        let reload = storage.reload_if_changed(path).await.unwrap().unwrap();
+        assert_eq!(reload.merged, 1);
+        assert_eq!(reload.conflicts.len(), 2);
+        let bread = storage.get_one_todo(1).unwrap();
+        assert_eq!(bread.notes, "integral");
+        assert!(bread.resolved);
+        assert_eq!(storage.get_one_todo(2).unwrap().notes, "desnatado");
+        remove_store(path).await;
+    }
+
+    #[tokio::test]
+    async fn test_renumber_compacts_keys() {
+        let path = "test_renumber_todo.txt";
+        let mut storage = factori::create!(Todos);
+        storage.insert_todo(Todo::new("bar\n".to_string()));
+        storage.insert_todo(Todo::new("baz\n".to_string()));
+        storage.remove(1);
+        storage.remove(3);
+        assert_eq!(storage.renumber(), 2);
+        assert_eq!(storage.renumber(), 0);
+        assert_eq!(
+            storage
+                .todo_collection
+                .keys()
+                .cloned()
+                .collect::<Vec<u32>>(),
+            vec![1, 2]
+        );
+        assert_eq!(&storage.get_one_todo(2).unwrap().message, "baz\n");
+        storage.parse_map_write_file(path).await.unwrap();
+
+        let mut reloaded = Todos::new();
+        reloaded.parse_file_for_todos(path).await.unwrap();
+        assert_eq!(&reloaded.get_one_todo(1).unwrap().message, "boo\n");
+        assert_eq!(&reloaded.get_one_todo(2).unwrap().message, "baz\n");
+
+        storage.insert_todo(Todo::new("qux\n".to_string()));
+        assert_eq!(&storage.get_one_todo(3).unwrap().message, "qux\n");
+        assert_eq!(storage.undo(2), 2);
+        assert_eq!(&storage.get_one_todo(3).unwrap().message, "bar\n");
+        assert_eq!(&storage.get_one_todo(2).unwrap().message, "baz\n");
+        assert_eq!(storage.undo(1), 1);
+        assert_eq!(&storage.get_one_todo(4).unwrap().message, "foo");
+        assert_eq!(storage.redo(1), 1);
+        assert!(storage.get_one_todo(4).is_none());
+        remove_store(path).await;
+    }
+
+    #[tokio::test]
+    async fn test_manual_order_and_pins_are_kept() {
+        let path = "test_order_todo.txt";
+        let mut storage = factori::create!(Todos);
+        storage.insert_todo(Todo::new("pagar conta".to_string()));
+        storage.insert_todo(Todo::new("lavar louça".to_string()));
+        let order = |storage: &Todos| {
+            storage
+                .page(&Query::default(), &Sort::default(), 0, 10)
+                .todos
+                .iter()
+                .map(|(key, _)| *key)
+                .collect::<Vec<u32>>()
+        };
+
+        assert!(storage.move_todo(4, Move::Top));
+        assert!(storage.move_todo(1, Move::Down));
+        assert!(!storage.move_todo(4, Move::Up));
+        assert_eq!(order(&storage), vec![4, 2, 1, 3]);
+        assert_eq!(storage.toggle_pin(3), Some(true));
+        assert_eq!(order(&storage), vec![3, 4, 2, 1]);
+        assert!(!storage.move_todo(3, Move::Bottom));
+        for _ in 0..20 {
+            assert!(storage.move_todo(1, Move::Up));
+            assert!(storage.move_todo(1, Move::Down));
+        }
+        assert_eq!(order(&storage), vec![3, 4, 2, 1]);
+
+        assert_eq!(storage.undo(1), 1);
+        assert_eq!(order(&storage), vec![3, 4, 1, 2]);
+        storage.update(4, Todo::new("lavar louça e secar".to_string()));
+        storage.insert_todo(Todo::new("regar plantas".to_string()));
+        assert_eq!(order(&storage), vec![3, 4, 1, 2, 5]);
+        storage.parse_map_write_file(path).await.unwrap();
+
+        let mut reloaded = Todos::new();
+        reloaded.parse_file_for_todos(path).await.unwrap();
+        assert_eq!(order(&reloaded), vec![3, 4, 1, 2, 5]);
+        assert!(reloaded.get_one_todo(3).unwrap().pinned);
+        storage.remove(2);
+        assert_eq!(storage.renumber(), 3);
+        assert_eq!(order(&storage), vec![2, 3, 1, 4]);
+        remove_store(path).await;
+    }
+
+    #[test]
+    fn test_respacing_is_undone_with_the_move() {
+        let mut storage = factori::create!(Todos);
+        storage.insert_todo(Todo::new("pagar conta".to_string()));
+        for (key, position) in [(1, 10), (2, 11), (3, 12)] {
+            let mut todo = storage.get_one_todo(key).unwrap();
+            todo.position = Some(position);
+            storage.set(key, Some(todo));
+        }
+        let positions = |storage: &Todos| {
+            storage
+                .todo_collection
+                .values()
+                .map(|todo| todo.position)
+                .collect::<Vec<Option<i64>>>()
+        };
+
+        assert!(storage.move_todo(3, Move::Up));
+        assert_eq!(
+            positions(&storage),
+            vec![Some(1024), Some(2048), Some(1536)]
+        );
+        assert_eq!(storage.undo(1), 1);
+        assert_eq!(positions(&storage), vec![Some(10), Some(11), Some(12)]);
+        assert_eq!(storage.redo(1), 1);
+        assert_eq!(
+            positions(&storage),
+            vec![Some(1024), Some(2048), Some(1536)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_views_are_saved_next_to_the_file() {
+        let path = "test_views_todo.txt";
+        let mut storage = factori::create!(Todos);
+        storage.save_view(View::new("hoje", "status:open due<=hoje"));
+        storage.save_view(View::new("sprint", "tag:sprint"));
+        assert!(storage.delete_view("sprint"));
+        storage.parse_map_write_file(path).await.unwrap();
+        assert!(Path::new(&Views::path(path)).exists());
+
+        let mut reloaded = Todos::new();
+        reloaded.parse_file_for_todos(path).await.unwrap();
+        let views = reloaded.views();
+        assert_eq!(views.len(), 1);
+        assert_eq!(views[0].query, "status:open due<=hoje");
+        remove_store(path).await;
+    }
+
+    #[tokio::test]
+    async fn test_trash_restore_and_expiry() {
+        let path = "test_trash_todo.txt";
+        let mut storage = factori::create!(Todos);
+        storage.remove(1);
+        assert!(storage.get_one_todo(1).is_none());
+        assert_eq!(storage.trashed()[0].key, 1);
+        assert_eq!(storage.undo(1), 1);
+        assert!(storage.trashed().is_empty());
+        assert_eq!(storage.redo(1), 1);
+        assert_eq!(storage.trashed().len(), 1);
+        storage.parse_map_write_file(path).await.unwrap();
+
+        let mut reloaded = Todos::new();
+        reloaded.parse_file_for_todos(path).await.unwrap();
+        assert_eq!(reloaded.trashed()[0].todo.message, "foo");
+        assert_eq!(reloaded.renumber(), 1);
+        assert_eq!(reloaded.restore_from_trash("foo"), Some(2));
+        assert_eq!(&reloaded.get_one_todo(2).unwrap().message, "foo");
+        assert!(reloaded.restore_from_trash("foo").is_none());
+        assert_eq!(reloaded.undo(1), 1);
+        assert_eq!(reloaded.trashed().len(), 1);
+
+        let mut expiring = Todos::new().with_trash_retention(0);
+        expiring.parse_file_for_todos(path).await.unwrap();
+        assert!(expiring.trashed().is_empty());
+        assert_eq!(reloaded.empty_trash(), 1);
+        assert_eq!(reloaded.empty_trash(), 0);
+        remove_store(path).await;
+    }
+
+    #[tokio::test]
+    async fn test_notes_are_saved_and_searched() {
+        let path = "test_notes_todo.txt";
+        let mut storage = factori::create!(Todos);
+        let mut bread = Todo::new("comprar pão\n".to_string());
+        bread.notes = "integral, da padaria".to_string();
+        storage.insert_todo(bread);
+        storage.insert_todo(Todo::new("abrir http://intranet/a // b\n".to_string()));
+        let mut foo = storage.get_one_todo(1).unwrap();
+        foo.notes = "lembrar do leite".to_string();
+        storage.update(1, foo);
+        storage.parse_map_write_file(path).await.unwrap();
+
+        let mut reloaded = Todos::new();
+        reloaded.parse_file_for_todos(path).await.unwrap();
+        let todo = reloaded.get_one_todo(3).unwrap();
+        assert_eq!(todo.message, "comprar pão\n");
+        assert_eq!(todo.notes, "integral, da padaria");
+        let todo = reloaded.get_one_todo(4).unwrap();
+        assert_eq!(todo.message, "abrir http://intranet/a // b\n");
+        assert!(todo.notes.is_empty());
+
+        let hits = reloaded.search(&Search::parse("PADARIA").unwrap());
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].key, 3);
+        let hits = reloaded.search(&Search::parse("re:le(ite)?").unwrap());
+        assert_eq!(hits[0].key, 1);
+        assert_eq!(reloaded.undo(1), 1);
+        assert!(reloaded.get_one_todo(1).unwrap().notes.is_empty());
+        remove_store(path).await;
+    }
+
+    #[test]
+    fn test_report_counts_resolved_todos_moved_to_trash() {
+        let mut storage = factori::create!(Todos);
+        let key = storage.insert_todo(Todo::new("fatura #trabalho".to_string()));
+        storage.resolve_one_todo(key);
+        storage.resolve_one_todo(2);
+        storage.remove(key);
+        let today = Local::now().date_naive();
+
+        let report = storage.report(today, today, Grouping::Tag);
+        assert_eq!(report.resolved, 2);
+        assert_eq!(report.groups[0].name, "#trabalho");
+        assert_eq!(
+            storage.restore_from_trash(&storage.trashed()[0].todo.id),
+            Some(key)
+        );
+        assert_eq!(storage.report(today, today, Grouping::Tag).resolved, 2);
+    }
+
+    #[test]
+    fn test_indexes_follow_changes() {
+        let mut storage = factori::create!(Todos);
+        storage.insert_todo(Todo::new("lavar louça #casa".to_string()));
+        storage.insert_todo(Todo::new("pagar conta #casa due:2023-05-10".to_string()));
+        let casa = query("tag:casa");
+        assert_eq!(storage.page(&casa, &Sort::default(), 0, 10).total, 2);
+
+        storage.update(3, Todo::new("lavar louça".to_string()));
+        storage.resolve_one_todo(4);
+        storage.remove(1);
+        assert_eq!(storage.page(&casa, &Sort::default(), 0, 10).todos[0].0, 4);
+        assert_eq!(
+            storage
+                .page(&query("status:done"), &Sort::default(), 0, 10)
+                .total,
+            1
+        );
+        assert_eq!(
+            storage
+                .page(&query("status:open"), &Sort::default(), 0, 10)
+                .total,
+            2
+        );
+
+        assert_eq!(storage.renumber(), 3);
+        let due = query("due<=2023-05-31");
+        assert_eq!(storage.page(&due, &Sort::default(), 0, 10).todos[0].0, 3);
+        let page = storage.page(&Query::default(), &Sort::default(), 1, 2);
+        assert_eq!(page.todos.len(), 1);
+        assert!(!page.has_next());
+        assert_eq!(storage.undo(1), 1);
+        assert_eq!(
+            storage
+                .page(&Query::default(), &Sort::default(), 0, 10)
+                .total,
+            4
+        );
+    }
+
+    #[test]
+    fn test_pages_follow_the_chosen_sort() {
+        let mut storage = factori::create!(Todos);
+        storage.insert_todo(Todo::new("pagar conta !high due:2023-05-10".to_string()));
+        storage.insert_todo(Todo::new("arrumar casa due:2023-05-01".to_string()));
+        let keys = |storage: &Todos, query: &str, sort: &str| {
+            let sort = Sort::parse(sort, Sort::default()).unwrap();
+            storage
+                .page(&self::query(query), &sort, 0, 10)
+                .todos
+                .iter()
+                .map(|(key, _)| *key)
+                .collect::<Vec<u32>>()
+        };
+
+        assert_eq!(keys(&storage, "", ""), vec![1, 2, 3, 4]);
+        assert_eq!(keys(&storage, "", "alfabetica"), vec![4, 2, 1, 3]);
+        assert_eq!(keys(&storage, "", "prazo desc"), vec![3, 4, 1, 2]);
+        assert_eq!(keys(&storage, "due<=2023-05-31", ""), vec![3, 4]);
+        assert_eq!(
+            keys(&storage, "-arrumar", "prioridade, alfabetica"),
+            vec![3, 2, 1]
+        );
+
+        assert!(storage.remember_sort("due<7d", Sort::parse("prazo", Sort::default()).unwrap()));
+        assert_eq!(storage.sort_for("due<7d").to_string(), "prazo ↑");
+    }
+
+    fn query(query: &str) -> Query {
+        Query::parse(query).unwrap()
+    }
+
+    async fn remove_store(path: &str) {
+        for file in [
+            path.to_string(),
+            History::path(path),
+            Trash::path(path),
+            Views::path(path),
+            Journal::log_path(path),
+            Journal::archive_path(path),
+        ] {
+            let _ = tokio::fs::remove_file(file).await;
+        }
     }
 }
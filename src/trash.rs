@@ -0,0 +1,73 @@
+use crate::todo::Todo;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TrashItem {
+    pub key: u32,
+    pub todo: Todo,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Trash {
+    items: Vec<TrashItem>,
+}
+
+impl Trash {
+    pub fn path(todo_path: &str) -> String {
+        format!("{todo_path}.trash")
+    }
+
+    pub fn put(&mut self, key: u32, todo: Todo, at: DateTime<Utc>) {
+        self.take(&todo.id);
+        self.items.push(TrashItem { key, todo, at });
+    }
+
+    pub fn take(&mut self, id: &str) -> Option<TrashItem> {
+        let position = self.items.iter().position(|item| item.todo.id == id)?;
+        Some(self.items.remove(position))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&TrashItem> {
+        self.items.iter().find(|item| item.todo.id == id)
+    }
+
+    pub fn items(&self) -> &[TrashItem] {
+        &self.items
+    }
+
+    pub fn expired(&self, now: DateTime<Utc>, retention: Duration) -> Vec<String> {
+        self.items
+            .iter()
+            .filter(|item| item.at + retention <= now)
+            .map(|item| item.todo.id.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_trash_keeps_one_item_per_todo_and_expires_old_ones() {
+        let day = |day| Utc.with_ymd_and_hms(2023, 1, day, 12, 0, 0).unwrap();
+        let milk = Todo::new("milk".to_string());
+        let bread = Todo::new("bread".to_string());
+        let mut trash = Trash::default();
+        trash.put(1, milk.clone(), day(1));
+        trash.put(2, bread.clone(), day(10));
+        trash.put(3, milk.clone(), day(2));
+
+        assert_eq!(trash.items().len(), 2);
+        assert_eq!(trash.get(&milk.id).unwrap().key, 3);
+        assert_eq!(
+            trash.expired(day(20), Duration::days(15)),
+            vec![milk.id.clone()]
+        );
+        assert_eq!(trash.take(&bread.id).unwrap().key, 2);
+        assert!(trash.get(&bread.id).is_none());
+    }
+}
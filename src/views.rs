@@ -0,0 +1,109 @@
+use crate::sort::Sort;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct View {
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub sort: Sort,
+}
+
+impl View {
+    pub fn new(name: &str, query: &str) -> Self {
+        Self {
+            name: Self::normalize(name),
+            query: query.trim().to_string(),
+            sort: Sort::default(),
+        }
+    }
+
+    pub fn with_sort(mut self, sort: Sort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    pub fn normalize(name: &str) -> String {
+        name.trim().trim_start_matches('@').to_lowercase()
+    }
+}
+
+#[cfg_attr(test, mockall::automock)]
+pub trait ViewStorage {
+    fn views(&self) -> Vec<View>;
+    fn save_view(&mut self, view: View);
+    fn delete_view(&mut self, name: &str) -> bool;
+    fn sort_for(&self, list: &str) -> Sort;
+    fn remember_sort(&mut self, list: &str, sort: Sort) -> bool;
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Views {
+    views: Vec<View>,
+    #[serde(default)]
+    sorts: BTreeMap<String, Sort>,
+}
+
+impl Views {
+    pub fn path(todo_path: &str) -> String {
+        format!("{todo_path}.views")
+    }
+
+    pub fn save(&mut self, view: View) {
+        match self.views.iter_mut().find(|saved| saved.name == view.name) {
+            Some(saved) => *saved = view,
+            None => self.views.push(view),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.views.len();
+        self.views.retain(|view| view.name != name);
+        self.views.len() < before
+    }
+
+    pub fn list(&self) -> &[View] {
+        &self.views
+    }
+
+    pub fn sort_for(&self, list: &str) -> Sort {
+        self.sorts.get(list.trim()).copied().unwrap_or_default()
+    }
+
+    pub fn remember_sort(&mut self, list: &str, sort: Sort) -> bool {
+        let list = list.trim().to_string();
+        match sort.is_manual() {
+            true => self.sorts.remove(&list).is_some(),
+            false => self.sorts.insert(list, sort) != Some(sort),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_views_are_saved_by_name() {
+        let mut views = Views::default();
+        views.save(View::new(" Hoje ", "status:open due<=hoje"));
+        views.save(View::new("sprint", "tag:sprint"));
+        views.save(View::new("@hoje", "due<=hoje "));
+
+        assert_eq!(views.list().len(), 2);
+        assert_eq!(views.list()[0].name, "hoje");
+        assert_eq!(views.list()[0].query, "due<=hoje");
+        assert!(views.remove("sprint"));
+        assert!(!views.remove("sprint"));
+        assert_eq!(views.list().len(), 1);
+
+        let sort = Sort::parse("prazo desc", Sort::default()).unwrap();
+        assert!(views.remember_sort("tag:casa ", sort));
+        assert!(!views.remember_sort("tag:casa", sort));
+        assert!(views.sort_for("tag:casa") == sort);
+        assert!(views.sort_for("").is_manual());
+        assert!(views.remember_sort("tag:casa", Sort::default()));
+        assert!(views.sort_for("tag:casa").is_manual());
+    }
+}
@@ -0,0 +1,65 @@
+use std::time::SystemTime;
+use tokio::fs::metadata;
+
+#[derive(Clone, PartialEq)]
+pub struct Fingerprint(Vec<Option<(u64, SystemTime)>>);
+
+impl Fingerprint {
+    pub async fn of(files: &[String]) -> Self {
+        let mut stamps = Vec::new();
+        for file in files {
+            let stamp = match metadata(file).await {
+                Ok(meta) => meta.modified().ok().map(|modified| (meta.len(), modified)),
+                Err(_) => None,
+            };
+            stamps.push(stamp);
+        }
+        Self(stamps)
+    }
+}
+
+#[derive(Default)]
+pub struct Reload {
+    pub merged: usize,
+    pub rekeyed: Vec<(u32, u32)>,
+    pub flagged: Vec<String>,
+    pub conflicts: Vec<String>,
+}
+
+impl Reload {
+    pub fn absorb(&mut self, other: Reload) {
+        self.merged += other.merged;
+        self.rekeyed.extend(other.rekeyed);
+        self.flagged.extend(other.flagged);
+        self.conflicts.extend(other.conflicts);
+    }
+}
+
+#[derive(Default)]
+pub struct Watcher {
+    fingerprint: Option<Fingerprint>,
+    external: Option<Reload>,
+}
+
+impl Watcher {
+    pub async fn mark(&mut self, files: &[String]) {
+        self.fingerprint = Some(Fingerprint::of(files).await);
+    }
+
+    pub async fn is_changed(&self, files: &[String]) -> bool {
+        match &self.fingerprint {
+            Some(fingerprint) => *fingerprint != Fingerprint::of(files).await,
+            None => false,
+        }
+    }
+
+    pub fn absorb(&mut self, reload: Reload) {
+        self.external
+            .get_or_insert_with(Reload::default)
+            .absorb(reload);
+    }
+
+    pub fn take(&mut self) -> Option<Reload> {
+        self.external.take()
+    }
+}